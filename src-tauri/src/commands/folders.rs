@@ -1,7 +1,12 @@
-use crate::database::models::folder::FolderWithChildren;
+use crate::commands::saved_searches::load_smart_folders;
+use crate::database::models::folder::{
+    FolderDeleteMode, FolderDeleteSummary, FolderPath, FolderTree, FolderWithChildren,
+};
 use crate::database::repository::folders_repository::{
     CreateFolderDto, FolderRepository, UpdateFolderDto,
 };
+use crate::database::repository::notes_repository::NoteRepository;
+use crate::database::repository::saved_searches_repository::SavedSearchRepository;
 use crate::utils::error::{AppError, Result};
 use serde::{Deserialize, Serialize};
 use tauri::State;
@@ -74,6 +79,20 @@ pub async fn update_folder(
     Ok(folder)
 }
 
+// Move a folder under another folder, or to the top level without a parent
+#[tauri::command]
+pub async fn move_folder(
+    folder_id: i32,
+    parent_folder_id: Option<i32>,
+    repository: State<'_, FolderRepository>,
+) -> Result<FolderWithChildren> {
+    let user_id = 1; // TODO: Get from auth
+    let folder = repository
+        .move_folder(folder_id, user_id, parent_folder_id)
+        .await?;
+    Ok(folder)
+}
+
 #[tauri::command]
 pub async fn delete_folder(
     folder_id: i32,
@@ -84,11 +103,85 @@ pub async fn delete_folder(
     Ok(true)
 }
 
+// Delete a folder along with its contents, see `FolderDeleteMode`
+#[tauri::command]
+pub async fn delete_folder_with_contents(
+    folder_id: i32,
+    mode: FolderDeleteMode,
+    repository: State<'_, FolderRepository>,
+) -> Result<FolderDeleteSummary> {
+    let user_id = 1; // TODO: Get from auth
+    let summary = repository
+        .delete_folder_with_contents(folder_id, user_id, mode)
+        .await?;
+    Ok(summary)
+}
+
+// Deep-copy a folder with its subfolders and notes
+#[tauri::command]
+pub async fn copy_folder(
+    folder_id: i32,
+    new_parent_id: Option<i32>,
+    copy_attachments: Option<bool>,
+    repository: State<'_, FolderRepository>,
+    notes: State<'_, NoteRepository>,
+) -> Result<FolderWithChildren> {
+    let user_id = 1; // TODO: Get from auth
+    let folder = repository
+        .copy_folder(
+            folder_id,
+            user_id,
+            new_parent_id,
+            copy_attachments.unwrap_or(false),
+            &notes,
+        )
+        .await?;
+    Ok(folder)
+}
+
+// Paths look like `Work/Clients/Acme`, see `utils::folder_path`
+#[tauri::command]
+pub async fn resolve_folder_path(
+    path: String,
+    repository: State<'_, FolderRepository>,
+) -> Result<FolderPath> {
+    let user_id = 1; // TODO: Get from auth
+    let folders = repository.resolve_folder_path(user_id, &path).await?;
+    Ok(folders)
+}
+
+#[tauri::command]
+pub async fn ensure_folder_path(
+    path: String,
+    repository: State<'_, FolderRepository>,
+) -> Result<FolderPath> {
+    let user_id = 1; // TODO: Get from auth
+    let folders = repository.ensure_folder_path(user_id, &path).await?;
+    Ok(folders)
+}
+
+#[tauri::command]
+pub async fn get_folder_path(
+    folder_id: i32,
+    repository: State<'_, FolderRepository>,
+) -> Result<FolderPath> {
+    let user_id = 1; // TODO: Get from auth
+    let folders = repository.get_folder_path(folder_id, user_id).await?;
+    Ok(folders)
+}
+
+// Real folders plus pinned saved searches shown as smart folders
 #[tauri::command]
 pub async fn get_folder_tree(
     repository: State<'_, FolderRepository>,
-) -> Result<Vec<FolderWithChildren>> {
+    saved_searches: State<'_, SavedSearchRepository>,
+    notes: State<'_, NoteRepository>,
+) -> Result<FolderTree> {
     let user_id = 1; // TODO: Get from auth
-    let tree = repository.get_folder_tree(user_id).await?;
-    Ok(tree)
+    let folders = repository.get_folder_tree(user_id).await?;
+    let smart_folders = load_smart_folders(user_id, &saved_searches, &notes).await?;
+    Ok(FolderTree {
+        folders,
+        smart_folders,
+    })
 }
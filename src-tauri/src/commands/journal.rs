@@ -0,0 +1,115 @@
+use crate::commands::templates::{render_note_from_template, CreateNoteFromTemplateRequest};
+use crate::config::settings::AppSettings;
+use crate::database::models::journal::{AdjacentDailyNotes, DailyNoteSummary};
+use crate::database::models::note::NoteWithRelations;
+use crate::database::repository::folders_repository::FolderRepository;
+use crate::database::repository::notes_repository::{CreateNoteDto, NoteRepository};
+use crate::database::repository::templates_repository::TemplateRepository;
+use crate::utils::error::{AppError, Result};
+use crate::utils::template::validate_date_format;
+use chrono::{Local, Months, NaiveDate, TimeZone};
+use std::fmt::Write;
+use tauri::State;
+
+// Open the journal note for `date` (today when omitted), creating it from the
+// configured folder, title format and template on first open
+#[tauri::command]
+pub async fn open_daily_note(
+    date: Option<NaiveDate>,
+    notes: State<'_, NoteRepository>,
+    folders: State<'_, FolderRepository>,
+    templates: State<'_, TemplateRepository>,
+) -> Result<NoteWithRelations> {
+    let user_id = 1; // TODO: Get from auth
+    let settings = AppSettings::load();
+    let now = Local::now();
+    let date = date.unwrap_or_else(|| now.date_naive());
+
+    if let Some(note) = notes.find_daily_note(user_id, date).await? {
+        return Ok(note);
+    }
+
+    // Time and zone specifiers parse fine but cannot be filled from a date,
+    // which only shows up as a formatting error
+    let format = &settings.journal_title_format;
+    validate_date_format(format)?;
+    let mut title = String::new();
+    write!(title, "{}", date.format(format)).map_err(|_| {
+        AppError::ValidationError(format!("Invalid journal title format '{}'", format))
+    })?;
+
+    let folder_name = settings.journal_folder.trim();
+    let folder_id = if folder_name.is_empty() {
+        None
+    } else {
+        Some(
+            folders
+                .find_or_create_root_folder(user_id, folder_name)
+                .await?
+                .folder_id,
+        )
+    };
+
+    let template = match settings.journal_template_id {
+        Some(template_id) => templates.get_template(template_id, user_id).await?,
+        None => None,
+    };
+
+    let (dto, tags) = match template {
+        Some(template) => {
+            // `{{date}}` in the template is the journal day, not the day it was opened
+            let moment = Local
+                .from_local_datetime(&date.and_time(now.time()))
+                .earliest()
+                .unwrap_or(now);
+            let request = CreateNoteFromTemplateRequest {
+                template_id: template.template_id,
+                title: Some(title),
+                folder_id,
+                ..Default::default()
+            };
+            render_note_from_template(&template, request, user_id, moment, &folders).await?
+        }
+        None => (
+            CreateNoteDto {
+                user_id,
+                title,
+                content: String::new(),
+                folder_id,
+                is_pinned: false,
+                template_id: None,
+            },
+            Vec::new(),
+        ),
+    };
+
+    let note = notes.create_daily_note(date, dto, &tags).await?;
+    Ok(note)
+}
+
+// Journal entries in a calendar month, given as `YYYY-MM`
+#[tauri::command]
+pub async fn list_daily_notes(
+    month: String,
+    notes: State<'_, NoteRepository>,
+) -> Result<Vec<DailyNoteSummary>> {
+    let user_id = 1; // TODO: Get from auth
+    let from =
+        NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d").map_err(|_| {
+            AppError::ValidationError(format!("Invalid month '{}' (expected YYYY-MM)", month))
+        })?;
+    let until = from + Months::new(1);
+
+    let entries = notes.list_daily_notes(user_id, from, until).await?;
+    Ok(entries)
+}
+
+#[tauri::command]
+pub async fn get_adjacent_daily_notes(
+    date: NaiveDate,
+    notes: State<'_, NoteRepository>,
+) -> Result<AdjacentDailyNotes> {
+    let user_id = 1; // TODO: Get from auth
+    let adjacent = notes.get_adjacent_daily_notes(user_id, date).await?;
+    Ok(adjacent)
+}
@@ -0,0 +1,47 @@
+use crate::database::models::link::{Backlink, LinkRewritePreview, OutgoingLinks, UnresolvedLink};
+use crate::database::repository::links_repository::LinkRepository;
+use crate::utils::error::Result;
+use tauri::State;
+
+#[tauri::command]
+pub async fn get_backlinks(
+    note_id: i32,
+    repository: State<'_, LinkRepository>,
+) -> Result<Vec<Backlink>> {
+    let user_id = 1; // TODO: Get from auth
+    let backlinks = repository.get_backlinks(note_id, user_id).await?;
+    Ok(backlinks)
+}
+
+#[tauri::command]
+pub async fn get_outgoing_links(
+    note_id: i32,
+    repository: State<'_, LinkRepository>,
+) -> Result<OutgoingLinks> {
+    let user_id = 1; // TODO: Get from auth
+    let links = repository.get_outgoing_links(note_id, user_id).await?;
+    Ok(links)
+}
+
+#[tauri::command]
+pub async fn get_unresolved_links(
+    repository: State<'_, LinkRepository>,
+) -> Result<Vec<UnresolvedLink>> {
+    let user_id = 1; // TODO: Get from auth
+    let links = repository.get_unresolved_links(user_id).await?;
+    Ok(links)
+}
+
+// Dry run for `update_note` with `rewrite_links`: the notes whose links would be rewritten
+#[tauri::command]
+pub async fn preview_link_rewrite(
+    note_id: i32,
+    new_title: String,
+    repository: State<'_, LinkRepository>,
+) -> Result<LinkRewritePreview> {
+    let user_id = 1; // TODO: Get from auth
+    let preview = repository
+        .preview_link_rewrite(note_id, user_id, &new_title)
+        .await?;
+    Ok(preview)
+}
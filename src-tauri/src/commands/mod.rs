@@ -3,10 +3,32 @@ pub mod folders;
 pub mod tags;
 pub mod users;
 pub mod attachments;
+pub mod links;
+pub mod revisions;
+pub mod templates;
+pub mod journal;
+pub mod reminders;
+pub mod tasks;
+pub mod render;
+pub mod properties;
+pub mod saved_searches;
+pub mod related;
+pub mod trash;
 
 // Re-exports
 pub use notes::*;
 pub use folders::*;
 pub use tags::*;
 pub use users::*;
-pub use attachments::*;
\ No newline at end of file
+pub use attachments::*;
+pub use links::*;
+pub use revisions::*;
+pub use templates::*;
+pub use journal::*;
+pub use reminders::*;
+pub use tasks::*;
+pub use render::*;
+pub use properties::*;
+pub use saved_searches::*;
+pub use related::*;
+pub use trash::*;
\ No newline at end of file
@@ -1,6 +1,12 @@
+use crate::commands::revisions::apply_revision_retention;
+use crate::database::models::bulk::{BulkNoteOperations, BulkUpdateSummary};
 use crate::database::models::note::NoteWithRelations;
+use crate::database::models::note_query::{NotePage, NoteQuery, NoteSortField};
+use crate::database::models::search::SearchResult;
 use crate::database::repository::notes_repository::{CreateNoteDto, NoteRepository, UpdateNoteDto};
+use crate::database::repository::revisions_repository::RevisionRepository;
 use crate::utils::error::{AppError, Result};
+use crate::utils::search_query::SearchExpr;
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
@@ -22,6 +28,28 @@ pub struct UpdateNoteRequest {
     pub tags: Option<Vec<String>>,
     pub is_pinned: Option<bool>,
     pub is_archived: Option<bool>,
+    pub sort_order: Option<i32>,
+    #[serde(default)]
+    pub rewrite_links: bool,
+    // Version the edit is based on; a newer stored version is a conflict
+    #[serde(default)]
+    pub expected_version: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DuplicateNoteRequest {
+    pub note_id: i32,
+    // Omitted to keep the original's folder, null for the top level
+    #[serde(default, with = "serde_with::rust::double_option")]
+    pub target_folder_id: Option<Option<i32>>,
+    #[serde(default)]
+    pub copy_attachments: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkUpdateNotesRequest {
+    pub note_ids: Vec<i32>,
+    pub operations: BulkNoteOperations,
 }
 
 #[tauri::command]
@@ -35,6 +63,7 @@ pub async fn create_note(
         content: request.content,
         folder_id: request.folder_id,
         is_pinned: request.is_pinned,
+        template_id: None,
     };
 
     let note = repository.create_note(dto, &request.tags).await?;
@@ -55,10 +84,13 @@ pub async fn get_note(
 
 #[tauri::command]
 pub async fn get_all_notes(
+    query: Option<NoteQuery>,
     repository: State<'_, NoteRepository>,
-) -> Result<Vec<NoteWithRelations>> {
+) -> Result<NotePage> {
     let user_id = 1; // TODO: Get from auth
-    let notes = repository.get_user_notes(user_id).await?;
+    let notes = repository
+        .get_user_notes(user_id, &query.unwrap_or_default())
+        .await?;
     Ok(notes)
 }
 
@@ -66,6 +98,7 @@ pub async fn get_all_notes(
 pub async fn update_note(
     request: UpdateNoteRequest,
     repository: State<'_, NoteRepository>,
+    revisions: State<'_, RevisionRepository>,
 ) -> Result<NoteWithRelations> {
     let dto = UpdateNoteDto {
         note_id: request.note_id,
@@ -74,9 +107,13 @@ pub async fn update_note(
         folder_id: request.folder_id,
         is_pinned: request.is_pinned,
         is_archived: request.is_archived,
+        sort_order: request.sort_order,
+        rewrite_links: request.rewrite_links,
+        expected_version: request.expected_version,
     };
 
     let note = repository.update_note(dto, request.tags).await?;
+    apply_revision_retention(&revisions, note.note.note_id).await?;
     Ok(note)
 }
 
@@ -87,41 +124,92 @@ pub async fn delete_note(note_id: i32, repository: State<'_, NoteRepository>) ->
     Ok(true)
 }
 
+#[tauri::command]
+pub async fn duplicate_note(
+    request: DuplicateNoteRequest,
+    repository: State<'_, NoteRepository>,
+) -> Result<NoteWithRelations> {
+    let user_id = 1; // TODO: Get from auth
+    let note = repository
+        .duplicate_note(
+            request.note_id,
+            user_id,
+            request.target_folder_id,
+            request.copy_attachments,
+        )
+        .await?;
+    Ok(note)
+}
+
 #[tauri::command]
 pub async fn search_notes(
     query: String,
+    options: Option<NoteQuery>,
     repository: State<'_, NoteRepository>,
-) -> Result<Vec<NoteWithRelations>> {
+) -> Result<NotePage<SearchResult>> {
     let user_id = 1; // TODO: Get from auth
-    let notes = repository.search_notes(user_id, &query).await?;
+    // Best matches first unless the caller asks for a different order
+    let options = options.unwrap_or_else(|| NoteQuery {
+        sort: NoteSortField::Relevance,
+        ..Default::default()
+    });
+    let search = SearchExpr::parse(&query)?;
+    let notes = repository.search_notes(user_id, &search, &options).await?;
+    Ok(notes)
+}
+
+#[tauri::command]
+pub async fn fuzzy_search_notes(
+    query: String,
+    options: Option<NoteQuery>,
+    repository: State<'_, NoteRepository>,
+) -> Result<NotePage<SearchResult>> {
+    let user_id = 1; // TODO: Get from auth
+    let options = options.unwrap_or_else(|| NoteQuery {
+        sort: NoteSortField::Relevance,
+        ..Default::default()
+    });
+    let search = SearchExpr::parse(&query)?;
+    let notes = repository
+        .fuzzy_search_notes(user_id, &search, &options)
+        .await?;
     Ok(notes)
 }
 
 #[tauri::command]
 pub async fn get_notes_by_folder(
     folder_id: i32,
+    query: Option<NoteQuery>,
     repository: State<'_, NoteRepository>,
-) -> Result<Vec<NoteWithRelations>> {
+) -> Result<NotePage> {
     let user_id = 1; // TODO: Get from auth
-    let notes = repository.get_notes_by_folder(user_id, folder_id).await?;
+    let notes = repository
+        .get_notes_by_folder(user_id, folder_id, &query.unwrap_or_default())
+        .await?;
     Ok(notes)
 }
 
 #[tauri::command]
 pub async fn get_pinned_notes(
+    query: Option<NoteQuery>,
     repository: State<'_, NoteRepository>,
-) -> Result<Vec<NoteWithRelations>> {
+) -> Result<NotePage> {
     let user_id = 1; // TODO: Get from auth
-    let notes = repository.get_pinned_notes(user_id).await?;
+    let notes = repository
+        .get_pinned_notes(user_id, &query.unwrap_or_default())
+        .await?;
     Ok(notes)
 }
 
 #[tauri::command]
 pub async fn get_archived_notes(
+    query: Option<NoteQuery>,
     repository: State<'_, NoteRepository>,
-) -> Result<Vec<NoteWithRelations>> {
+) -> Result<NotePage> {
     let user_id = 1; // TODO: Get from auth
-    let notes = repository.get_archived_notes(user_id).await?;
+    let notes = repository
+        .get_archived_notes(user_id, &query.unwrap_or_default())
+        .await?;
     Ok(notes)
 }
 
@@ -138,6 +226,9 @@ pub async fn toggle_note_pin(note_id: i32, repository: State<'_, NoteRepository>
             folder_id: None,
             is_pinned: Some(!note.note.is_pinned),
             is_archived: None,
+            sort_order: None,
+            rewrite_links: false,
+            expected_version: None,
         };
         repository.update_note(dto, None).await?;
         Ok(true)
@@ -162,6 +253,9 @@ pub async fn toggle_note_archive(
             folder_id: None,
             is_pinned: None,
             is_archived: Some(!note.note.is_archived),
+            sort_order: None,
+            rewrite_links: false,
+            expected_version: None,
         };
         repository.update_note(dto, None).await?;
         Ok(true)
@@ -169,3 +263,17 @@ pub async fn toggle_note_archive(
         Err(AppError::NotFound("Note not found".to_string()))
     }
 }
+
+// Move, tag, untag, archive, pin or trash many notes at once. Notes that
+// cannot be updated are reported per note instead of failing the batch.
+#[tauri::command]
+pub async fn bulk_update_notes(
+    request: BulkUpdateNotesRequest,
+    repository: State<'_, NoteRepository>,
+) -> Result<BulkUpdateSummary> {
+    let user_id = 1; // TODO: Get from auth
+    let summary = repository
+        .bulk_update_notes(user_id, &request.note_ids, &request.operations)
+        .await?;
+    Ok(summary)
+}
@@ -0,0 +1,38 @@
+use crate::database::models::note::NoteWithRelations;
+use crate::database::models::property::{NoteProperty, PropertyKey, PropertyNoteQuery};
+use crate::database::repository::notes_repository::NoteRepository;
+use crate::database::repository::properties_repository::PropertyRepository;
+use crate::utils::error::Result;
+use tauri::State;
+
+#[tauri::command]
+pub async fn get_note_properties(
+    note_id: i32,
+    repository: State<'_, PropertyRepository>,
+) -> Result<Vec<NoteProperty>> {
+    let user_id = 1; // TODO: Get from auth
+    let properties = repository.get_note_properties(note_id, user_id).await?;
+    Ok(properties)
+}
+
+#[tauri::command]
+pub async fn list_property_keys(
+    repository: State<'_, PropertyRepository>,
+) -> Result<Vec<PropertyKey>> {
+    let user_id = 1; // TODO: Get from auth
+    let keys = repository.list_property_keys(user_id).await?;
+    Ok(keys)
+}
+
+// Filter and sort notes by frontmatter values, e.g. `status = draft AND priority >= 2`
+#[tauri::command]
+pub async fn query_notes_by_properties(
+    query: PropertyNoteQuery,
+    repository: State<'_, NoteRepository>,
+) -> Result<Vec<NoteWithRelations>> {
+    let user_id = 1; // TODO: Get from auth
+    let notes = repository
+        .query_notes_by_properties(user_id, &query)
+        .await?;
+    Ok(notes)
+}
@@ -0,0 +1,17 @@
+use crate::database::models::related::RelatedNote;
+use crate::database::repository::similarity_repository::SimilarityRepository;
+use crate::utils::error::Result;
+use tauri::State;
+
+#[tauri::command]
+pub async fn get_related_notes(
+    note_id: i32,
+    limit: Option<u32>,
+    repository: State<'_, SimilarityRepository>,
+) -> Result<Vec<RelatedNote>> {
+    let user_id = 1; // TODO: Get from auth
+    let related = repository
+        .get_related_notes(note_id, user_id, limit)
+        .await?;
+    Ok(related)
+}
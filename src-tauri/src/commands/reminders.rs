@@ -0,0 +1,127 @@
+use crate::database::models::reminder::{DueReminder, NoteReminder, Recurrence};
+use crate::database::repository::reminders_repository::{
+    CreateReminderDto, ReminderRepository, UpdateReminderDto,
+};
+use crate::utils::error::Result;
+use crate::utils::reminder_time::parse_reminder_time;
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateReminderRequest {
+    pub note_id: i32,
+    // A timestamp or natural-language time such as "tomorrow 9am" or "in 2 hours"
+    pub remind_at: String,
+    pub recurrence: Option<Recurrence>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateReminderRequest {
+    pub reminder_id: i32,
+    pub remind_at: Option<String>,
+    // For both: omitted to keep the current value, null to clear it
+    #[serde(default, with = "serde_with::rust::double_option")]
+    pub recurrence: Option<Option<Recurrence>>,
+    #[serde(default, with = "serde_with::rust::double_option")]
+    pub message: Option<Option<String>>,
+    pub is_done: Option<bool>,
+}
+
+#[tauri::command]
+pub async fn create_reminder(
+    request: CreateReminderRequest,
+    repository: State<'_, ReminderRepository>,
+) -> Result<NoteReminder> {
+    let dto = CreateReminderDto {
+        note_id: request.note_id,
+        user_id: 1, // TODO: Get from auth
+        remind_at: parse_reminder_time(&request.remind_at, Local::now())?.with_timezone(&Utc),
+        recurrence: request.recurrence,
+        message: request.message,
+    };
+
+    let reminder = repository.create_reminder(dto).await?;
+    Ok(reminder)
+}
+
+#[tauri::command]
+pub async fn get_note_reminders(
+    note_id: i32,
+    repository: State<'_, ReminderRepository>,
+) -> Result<Vec<NoteReminder>> {
+    let user_id = 1; // TODO: Get from auth
+    let reminders = repository.get_note_reminders(note_id, user_id).await?;
+    Ok(reminders)
+}
+
+#[tauri::command]
+pub async fn get_upcoming_reminders(
+    repository: State<'_, ReminderRepository>,
+) -> Result<Vec<DueReminder>> {
+    let user_id = 1; // TODO: Get from auth
+    let reminders = repository.get_upcoming_reminders(user_id).await?;
+    Ok(reminders)
+}
+
+#[tauri::command]
+pub async fn update_reminder(
+    request: UpdateReminderRequest,
+    repository: State<'_, ReminderRepository>,
+) -> Result<NoteReminder> {
+    let remind_at = match request.remind_at {
+        Some(text) => Some(parse_reminder_time(&text, Local::now())?.with_timezone(&Utc)),
+        None => None,
+    };
+
+    let dto = UpdateReminderDto {
+        reminder_id: request.reminder_id,
+        user_id: 1, // TODO: Get from auth
+        remind_at,
+        recurrence: request.recurrence,
+        message: request.message,
+        is_done: request.is_done,
+    };
+
+    let reminder = repository.update_reminder(dto).await?;
+    Ok(reminder)
+}
+
+#[tauri::command]
+pub async fn delete_reminder(
+    reminder_id: i32,
+    repository: State<'_, ReminderRepository>,
+) -> Result<bool> {
+    let user_id = 1; // TODO: Get from auth
+    repository.delete_reminder(reminder_id, user_id).await?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn get_missed_reminders(
+    repository: State<'_, ReminderRepository>,
+) -> Result<Vec<DueReminder>> {
+    let user_id = 1; // TODO: Get from auth
+    let reminders = repository.get_missed_reminders(user_id).await?;
+    Ok(reminders)
+}
+
+#[tauri::command]
+pub async fn dismiss_missed_reminders(
+    reminder_ids: Vec<i32>,
+    repository: State<'_, ReminderRepository>,
+) -> Result<u64> {
+    let user_id = 1; // TODO: Get from auth
+    let dismissed = repository
+        .dismiss_missed_reminders(&reminder_ids, user_id)
+        .await?;
+    Ok(dismissed)
+}
+
+// Resolve a natural-language reminder time so the UI can show it before saving
+#[tauri::command]
+pub async fn parse_reminder_text(text: String) -> Result<DateTime<Utc>> {
+    let time = parse_reminder_time(&text, Local::now())?;
+    Ok(time.with_timezone(&Utc))
+}
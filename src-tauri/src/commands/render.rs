@@ -0,0 +1,81 @@
+use crate::database::models::attachment::Attachment;
+use crate::database::models::note::Note;
+use crate::database::repository::attachments_repository::AttachmentRepository;
+use crate::database::repository::notes_repository::NoteRepository;
+use crate::utils::error::{AppError, Result};
+use crate::utils::markdown::{render_markdown, AttachmentLinks, RenderOptions, RenderedHtml};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+// Render a note for the preview pane. Attachment files the note links to
+// are added to the asset protocol scope so the webview can load them.
+#[tauri::command]
+pub async fn render_note_html<R: Runtime>(
+    note_id: i32,
+    options: Option<RenderOptions>,
+    app: AppHandle<R>,
+    notes: State<'_, NoteRepository>,
+    attachments: State<'_, AttachmentRepository>,
+) -> Result<RenderedHtml> {
+    let user_id = 1; // TODO: Get from auth
+    let options = options.unwrap_or_default();
+    let attachments = attachments.get_note_attachments(note_id).await?;
+    let (_, rendered) = render_note(note_id, user_id, &options, &notes, &attachments).await?;
+
+    if options.attachment_links == AttachmentLinks::Asset {
+        let scope = app.asset_protocol_scope();
+        for attachment in attachments
+            .iter()
+            .filter(|attachment| rendered.attachment_ids.contains(&attachment.attachment_id))
+        {
+            scope.allow_file(&attachment.file_path).map_err(|e| {
+                AppError::IoError(format!(
+                    "Failed to allow attachment {}: {}",
+                    attachment.file_path, e
+                ))
+            })?;
+        }
+    }
+
+    Ok(rendered)
+}
+
+// Write a note to disk as a standalone HTML page, for export or printing.
+// Attachments are linked with `file://` URLs so the page works outside the app.
+#[tauri::command]
+pub async fn export_note_html(
+    note_id: i32,
+    path: String,
+    options: Option<RenderOptions>,
+    notes: State<'_, NoteRepository>,
+    attachments: State<'_, AttachmentRepository>,
+) -> Result<()> {
+    let user_id = 1; // TODO: Get from auth
+    let options = RenderOptions {
+        attachment_links: AttachmentLinks::File,
+        ..options.unwrap_or_default()
+    };
+    let attachments = attachments.get_note_attachments(note_id).await?;
+    let (note, rendered) = render_note(note_id, user_id, &options, &notes, &attachments).await?;
+
+    tokio::fs::write(&path, rendered.to_document(&note.title)).await?;
+    Ok(())
+}
+
+// Shared by the preview and export paths
+pub(crate) async fn render_note(
+    note_id: i32,
+    user_id: i32,
+    options: &RenderOptions,
+    notes: &NoteRepository,
+    attachments: &[Attachment],
+) -> Result<(Note, RenderedHtml)> {
+    let note = notes
+        .get_note_by_id(note_id)
+        .await?
+        .filter(|note| note.note.user_id == user_id)
+        .ok_or_else(|| AppError::NotFound("Note not found".to_string()))?
+        .note;
+
+    let rendered = render_markdown(&note.content, options, attachments)?;
+    Ok((note, rendered))
+}
@@ -0,0 +1,167 @@
+use crate::config::settings::AppSettings;
+use crate::database::models::note::NoteWithRelations;
+use crate::database::models::revision::{NoteRevision, NoteRevisionSummary};
+use crate::database::repository::notes_repository::{NoteRepository, UpdateNoteDto};
+use crate::database::repository::revisions_repository::RevisionRepository;
+use crate::utils::diff::{diff_text, DiffChange, DiffGranularity};
+use crate::utils::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiffRevisionsRequest {
+    pub note_id: i32,
+    // `None` on either side means the note's current content
+    pub from_revision_id: Option<i32>,
+    pub to_revision_id: Option<i32>,
+    pub granularity: Option<DiffGranularity>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteDiff {
+    pub note_id: i32,
+    pub from_revision_id: Option<i32>,
+    pub to_revision_id: Option<i32>,
+    pub granularity: DiffGranularity,
+    pub title: Vec<DiffChange>,
+    pub content: Vec<DiffChange>,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+#[tauri::command]
+pub async fn get_note_history(
+    note_id: i32,
+    repository: State<'_, RevisionRepository>,
+) -> Result<Vec<NoteRevisionSummary>> {
+    let user_id = 1; // TODO: Get from auth
+    let history = repository.get_note_history(note_id, user_id).await?;
+    Ok(history)
+}
+
+#[tauri::command]
+pub async fn get_note_revision(
+    revision_id: i32,
+    repository: State<'_, RevisionRepository>,
+) -> Result<NoteRevision> {
+    let user_id = 1; // TODO: Get from auth
+    let revision = repository
+        .get_revision(revision_id, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Revision not found".to_string()))?;
+    Ok(revision)
+}
+
+#[tauri::command]
+pub async fn diff_note_revisions(
+    request: DiffRevisionsRequest,
+    revisions: State<'_, RevisionRepository>,
+    notes: State<'_, NoteRepository>,
+) -> Result<NoteDiff> {
+    let user_id = 1; // TODO: Get from auth
+    let granularity = request.granularity.unwrap_or_default();
+
+    let (old_title, old_content) = load_version(
+        &revisions,
+        &notes,
+        request.note_id,
+        request.from_revision_id,
+        user_id,
+    )
+    .await?;
+    let (new_title, new_content) = load_version(
+        &revisions,
+        &notes,
+        request.note_id,
+        request.to_revision_id,
+        user_id,
+    )
+    .await?;
+
+    let title = diff_text(&old_title, &new_title, DiffGranularity::Word);
+    let content = diff_text(&old_content, &new_content, granularity);
+
+    Ok(NoteDiff {
+        note_id: request.note_id,
+        from_revision_id: request.from_revision_id,
+        to_revision_id: request.to_revision_id,
+        granularity,
+        title: title.changes,
+        content: content.changes,
+        insertions: content.insertions,
+        deletions: content.deletions,
+    })
+}
+
+#[tauri::command]
+pub async fn restore_note_revision(
+    revision_id: i32,
+    revisions: State<'_, RevisionRepository>,
+    notes: State<'_, NoteRepository>,
+) -> Result<NoteWithRelations> {
+    let user_id = 1; // TODO: Get from auth
+    let revision = revisions
+        .get_revision(revision_id, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Revision not found".to_string()))?;
+
+    // Restoring goes through a regular update, so the content being replaced
+    // is itself kept as a revision and the restore can be undone.
+    let dto = UpdateNoteDto {
+        note_id: revision.note_id,
+        title: Some(revision.title),
+        content: Some(revision.content),
+        folder_id: None,
+        is_pinned: None,
+        is_archived: None,
+        sort_order: None,
+        rewrite_links: false,
+        expected_version: None,
+    };
+
+    let note = notes.update_note(dto, None).await?;
+    apply_revision_retention(&revisions, note.note.note_id).await?;
+    Ok(note)
+}
+
+pub(crate) async fn apply_revision_retention(
+    repository: &RevisionRepository,
+    note_id: i32,
+) -> Result<()> {
+    let settings = AppSettings::load();
+    repository
+        .prune_revisions(
+            note_id,
+            settings.revision_retention_count,
+            settings.revision_retention_days,
+        )
+        .await?;
+    Ok(())
+}
+
+async fn load_version(
+    revisions: &RevisionRepository,
+    notes: &NoteRepository,
+    note_id: i32,
+    revision_id: Option<i32>,
+    user_id: i32,
+) -> Result<(String, String)> {
+    match revision_id {
+        Some(revision_id) => {
+            let revision = revisions
+                .get_revision(revision_id, user_id)
+                .await?
+                .filter(|r| r.note_id == note_id)
+                .ok_or_else(|| AppError::NotFound("Revision not found".to_string()))?;
+            Ok((revision.title, revision.content))
+        }
+        None => {
+            let note = notes
+                .get_note_by_id(note_id)
+                .await?
+                .filter(|n| n.note.user_id == user_id)
+                .ok_or_else(|| AppError::NotFound("Note not found".to_string()))?;
+            Ok((note.note.title, note.note.content))
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use crate::database::models::note_query::{NotePage, NoteQuery};
+use crate::database::models::saved_search::{SavedSearch, SmartFolder};
+use crate::database::models::search::SearchResult;
+use crate::database::repository::notes_repository::NoteRepository;
+use crate::database::repository::saved_searches_repository::{
+    CreateSavedSearchDto, SavedSearchRepository, UpdateSavedSearchDto,
+};
+use crate::utils::error::{AppError, Result};
+use crate::utils::search_query::SearchExpr;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateSavedSearchRequest {
+    pub name: String,
+    pub query: String,
+    #[serde(default)]
+    pub options: NoteQuery,
+    #[serde(default)]
+    pub is_pinned: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateSavedSearchRequest {
+    pub saved_search_id: i32,
+    pub name: Option<String>,
+    pub query: Option<String>,
+    pub options: Option<NoteQuery>,
+    pub is_pinned: Option<bool>,
+    pub sidebar_order: Option<i32>,
+}
+
+#[tauri::command]
+pub async fn create_saved_search(
+    request: CreateSavedSearchRequest,
+    repository: State<'_, SavedSearchRepository>,
+) -> Result<SavedSearch> {
+    let dto = CreateSavedSearchDto {
+        user_id: 1, // TODO: Get from auth
+        name: request.name,
+        query: request.query,
+        options: request.options,
+        is_pinned: request.is_pinned,
+    };
+
+    let search = repository.create_saved_search(dto).await?;
+    Ok(search)
+}
+
+#[tauri::command]
+pub async fn list_saved_searches(
+    repository: State<'_, SavedSearchRepository>,
+) -> Result<Vec<SavedSearch>> {
+    let user_id = 1; // TODO: Get from auth
+    let searches = repository.list_saved_searches(user_id).await?;
+    Ok(searches)
+}
+
+#[tauri::command]
+pub async fn update_saved_search(
+    request: UpdateSavedSearchRequest,
+    repository: State<'_, SavedSearchRepository>,
+) -> Result<SavedSearch> {
+    let dto = UpdateSavedSearchDto {
+        saved_search_id: request.saved_search_id,
+        user_id: 1, // TODO: Get from auth
+        name: request.name,
+        query: request.query,
+        options: request.options,
+        is_pinned: request.is_pinned,
+        sidebar_order: request.sidebar_order,
+    };
+
+    let search = repository.update_saved_search(dto).await?;
+    Ok(search)
+}
+
+#[tauri::command]
+pub async fn delete_saved_search(
+    saved_search_id: i32,
+    repository: State<'_, SavedSearchRepository>,
+) -> Result<bool> {
+    let user_id = 1; // TODO: Get from auth
+    repository
+        .delete_saved_search(saved_search_id, user_id)
+        .await?;
+    Ok(true)
+}
+
+// Run a saved search with its stored filters and sort order. Only paging
+// comes from the caller.
+#[tauri::command]
+pub async fn execute_saved_search(
+    saved_search_id: i32,
+    cursor: Option<String>,
+    page_size: Option<u32>,
+    repository: State<'_, SavedSearchRepository>,
+    notes: State<'_, NoteRepository>,
+) -> Result<NotePage<SearchResult>> {
+    let user_id = 1; // TODO: Get from auth
+    let saved = repository
+        .get_saved_search(saved_search_id, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Saved search not found".to_string()))?;
+
+    let options = NoteQuery {
+        cursor,
+        page_size,
+        ..saved.options
+    };
+    let search = SearchExpr::parse(&saved.query)?;
+    let page = notes.search_notes(user_id, &search, &options).await?;
+    Ok(page)
+}
+
+// Pinned saved searches with live note counts, for the sidebar
+pub(crate) async fn load_smart_folders(
+    user_id: i32,
+    repository: &SavedSearchRepository,
+    notes: &NoteRepository,
+) -> Result<Vec<SmartFolder>> {
+    let mut folders = Vec::new();
+    for search in repository.get_pinned_searches(user_id).await? {
+        let expr = SearchExpr::parse(&search.query)?;
+        let note_count = notes
+            .count_search_results(user_id, &expr, &search.options)
+            .await?;
+        folders.push(SmartFolder { search, note_count });
+    }
+    Ok(folders)
+}
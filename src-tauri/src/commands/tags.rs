@@ -1,4 +1,7 @@
-use crate::database::models::tag::TagWithNotes;
+use crate::database::models::tag::{TagSuggestion, TagWithNotes};
+use crate::database::repository::similarity_repository::{
+    SimilarityRepository, TagSuggestionSource,
+};
 use crate::database::repository::tags_repository::{CreateTagDto, TagRepository, UpdateTagDto};
 use crate::utils::error::{AppError, Result};
 use serde::{Deserialize, Serialize};
@@ -17,6 +20,17 @@ pub struct UpdateTagRequest {
     pub color: Option<String>,
 }
 
+// Either an existing note, or a draft with the tags already chosen for it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SuggestTagsRequest {
+    pub note_id: Option<i32>,
+    pub draft_title: Option<String>,
+    pub draft_content: Option<String>,
+    #[serde(default)]
+    pub applied_tags: Vec<String>,
+    pub limit: Option<u32>,
+}
+
 #[tauri::command]
 pub async fn create_tag(
     request: CreateTagRequest,
@@ -98,3 +112,29 @@ pub async fn get_notes_by_tag(
     let notes = repository.get_notes_by_tag(tag_id).await?;
     Ok(notes)
 }
+
+#[tauri::command]
+pub async fn suggest_tags(
+    request: SuggestTagsRequest,
+    repository: State<'_, SimilarityRepository>,
+) -> Result<Vec<TagSuggestion>> {
+    let user_id = 1; // TODO: Get from auth
+    let source = match (request.note_id, request.draft_content) {
+        (Some(note_id), None) => TagSuggestionSource::Note(note_id),
+        (None, Some(content)) => TagSuggestionSource::Draft {
+            title: request.draft_title.unwrap_or_default(),
+            content,
+            applied_tags: request.applied_tags,
+        },
+        _ => {
+            return Err(AppError::ValidationError(
+                "Provide either a note or draft content".to_string(),
+            ))
+        }
+    };
+
+    let suggestions = repository
+        .suggest_tags(user_id, source, request.limit)
+        .await?;
+    Ok(suggestions)
+}
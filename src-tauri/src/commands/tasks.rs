@@ -0,0 +1,40 @@
+use crate::commands::revisions::apply_revision_retention;
+use crate::database::models::task::{NoteTask, OpenTask, TaskFilter};
+use crate::database::repository::revisions_repository::RevisionRepository;
+use crate::database::repository::tasks_repository::TaskRepository;
+use crate::utils::error::Result;
+use tauri::State;
+
+#[tauri::command]
+pub async fn get_open_tasks(
+    filter: Option<TaskFilter>,
+    repository: State<'_, TaskRepository>,
+) -> Result<Vec<OpenTask>> {
+    let user_id = 1; // TODO: Get from auth
+    let tasks = repository
+        .get_open_tasks(user_id, &filter.unwrap_or_default())
+        .await?;
+    Ok(tasks)
+}
+
+#[tauri::command]
+pub async fn get_note_tasks(
+    note_id: i32,
+    repository: State<'_, TaskRepository>,
+) -> Result<Vec<NoteTask>> {
+    let user_id = 1; // TODO: Get from auth
+    let tasks = repository.get_note_tasks(note_id, user_id).await?;
+    Ok(tasks)
+}
+
+#[tauri::command]
+pub async fn toggle_task(
+    task_id: i32,
+    repository: State<'_, TaskRepository>,
+    revisions: State<'_, RevisionRepository>,
+) -> Result<NoteTask> {
+    let user_id = 1; // TODO: Get from auth
+    let task = repository.toggle_task(task_id, user_id).await?;
+    apply_revision_retention(&revisions, task.note_id).await?;
+    Ok(task)
+}
@@ -0,0 +1,214 @@
+use crate::database::models::note::NoteWithRelations;
+use crate::database::models::template::{NoteTemplate, TemplatePrompt};
+use crate::database::repository::folders_repository::FolderRepository;
+use crate::database::repository::notes_repository::{CreateNoteDto, NoteRepository};
+use crate::database::repository::templates_repository::{
+    CreateTemplateDto, TemplateRepository, UpdateTemplateDto,
+};
+use crate::utils::error::{AppError, Result};
+use crate::utils::template::{render_template, TemplateContext};
+use crate::utils::validation::validate_note_title;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::State;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateTemplateRequest {
+    pub name: String,
+    #[serde(default)]
+    pub title_template: String,
+    #[serde(default)]
+    pub content: String,
+    pub default_folder_id: Option<i32>,
+    #[serde(default)]
+    pub default_tags: Vec<String>,
+    #[serde(default)]
+    pub prompts: Vec<TemplatePrompt>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateTemplateRequest {
+    pub template_id: i32,
+    pub name: Option<String>,
+    pub title_template: Option<String>,
+    pub content: Option<String>,
+    // Omitted to keep the current default folder, null to clear it
+    #[serde(default, with = "serde_with::rust::double_option")]
+    pub default_folder_id: Option<Option<i32>>,
+    pub default_tags: Option<Vec<String>>,
+    pub prompts: Option<Vec<TemplatePrompt>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CreateNoteFromTemplateRequest {
+    pub template_id: i32,
+    // Used instead of the rendered title template when given
+    pub title: Option<String>,
+    // Used instead of the template's default folder when given
+    pub folder_id: Option<i32>,
+    // Added to the template's default tags
+    pub tags: Vec<String>,
+    // Answers to the template's prompts, by prompt name
+    pub values: HashMap<String, String>,
+}
+
+#[tauri::command]
+pub async fn create_template(
+    request: CreateTemplateRequest,
+    repository: State<'_, TemplateRepository>,
+) -> Result<NoteTemplate> {
+    let dto = CreateTemplateDto {
+        user_id: 1, // TODO: Get from auth
+        name: request.name,
+        title_template: request.title_template,
+        content: request.content,
+        default_folder_id: request.default_folder_id,
+        default_tags: request.default_tags,
+        prompts: request.prompts,
+    };
+
+    let template = repository.create_template(dto).await?;
+    Ok(template)
+}
+
+#[tauri::command]
+pub async fn list_templates(
+    repository: State<'_, TemplateRepository>,
+) -> Result<Vec<NoteTemplate>> {
+    let user_id = 1; // TODO: Get from auth
+    let templates = repository.list_templates(user_id).await?;
+    Ok(templates)
+}
+
+#[tauri::command]
+pub async fn update_template(
+    request: UpdateTemplateRequest,
+    repository: State<'_, TemplateRepository>,
+) -> Result<NoteTemplate> {
+    let dto = UpdateTemplateDto {
+        template_id: request.template_id,
+        user_id: 1, // TODO: Get from auth
+        name: request.name,
+        title_template: request.title_template,
+        content: request.content,
+        default_folder_id: request.default_folder_id,
+        default_tags: request.default_tags,
+        prompts: request.prompts,
+    };
+
+    let template = repository.update_template(dto).await?;
+    Ok(template)
+}
+
+#[tauri::command]
+pub async fn delete_template(
+    template_id: i32,
+    repository: State<'_, TemplateRepository>,
+) -> Result<bool> {
+    let user_id = 1; // TODO: Get from auth
+    repository.delete_template(template_id, user_id).await?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn create_note_from_template(
+    request: CreateNoteFromTemplateRequest,
+    templates: State<'_, TemplateRepository>,
+    notes: State<'_, NoteRepository>,
+    folders: State<'_, FolderRepository>,
+) -> Result<NoteWithRelations> {
+    let user_id = 1; // TODO: Get from auth
+    let template = templates
+        .get_template(request.template_id, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Template not found".to_string()))?;
+
+    let (dto, tags) =
+        render_note_from_template(&template, request, user_id, Local::now(), &folders).await?;
+    let note = notes.create_note(dto, &tags).await?;
+    Ok(note)
+}
+
+// Render a template into a new note. `now` fills `{{date}}` and `{{time}}`.
+// The template's default folder and tags are applied by `create_note`; the
+// default folder is only looked up here to fill `{{folder}}`.
+pub(crate) async fn render_note_from_template(
+    template: &NoteTemplate,
+    request: CreateNoteFromTemplateRequest,
+    user_id: i32,
+    now: DateTime<Local>,
+    folders: &FolderRepository,
+) -> Result<(CreateNoteDto, Vec<String>)> {
+    // An explicitly chosen folder must exist; a stale default is just dropped
+    let folder = match (request.folder_id, template.default_folder_id) {
+        (Some(folder_id), _) => Some(
+            folders
+                .find_user_folder(folder_id, user_id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Folder not found".to_string()))?,
+        ),
+        (None, Some(folder_id)) => folders.find_user_folder(folder_id, user_id).await?,
+        (None, None) => None,
+    };
+    let folder_name = folder.as_ref().map(|f| f.name.as_str()).unwrap_or_default();
+
+    let mut values = HashMap::new();
+    for prompt in &template.prompts {
+        let value = request
+            .values
+            .get(&prompt.name)
+            .filter(|value| !value.trim().is_empty())
+            .or(prompt.default_value.as_ref());
+
+        match value {
+            Some(value) => {
+                values.insert(prompt.name.clone(), value.clone());
+            }
+            None if prompt.required => {
+                return Err(AppError::ValidationError(format!(
+                    "'{}' is required",
+                    prompt.label
+                )));
+            }
+            None => {
+                values.insert(prompt.name.clone(), String::new());
+            }
+        }
+    }
+
+    let mut context = TemplateContext {
+        now,
+        title: "",
+        folder: folder_name,
+        values: &values,
+    };
+
+    let title = match request.title.filter(|title| !title.trim().is_empty()) {
+        Some(title) => title,
+        None => {
+            let rendered = render_template(&template.title_template, &context)?;
+            if rendered.trim().is_empty() {
+                template.name.clone()
+            } else {
+                rendered.trim().to_string()
+            }
+        }
+    };
+
+    validate_note_title(&title)?;
+    context.title = &title;
+    let content = render_template(&template.content, &context)?;
+
+    let dto = CreateNoteDto {
+        user_id,
+        title,
+        content,
+        folder_id: request.folder_id,
+        is_pinned: false,
+        template_id: Some(template.template_id),
+    };
+
+    Ok((dto, request.tags))
+}
@@ -0,0 +1,47 @@
+use crate::config::settings::AppSettings;
+use crate::database::models::trash::{PurgeSummary, TrashItemType, TrashedItem};
+use crate::database::repository::trash_repository::TrashRepository;
+use crate::utils::error::Result;
+use tauri::State;
+
+#[tauri::command]
+pub async fn get_trashed_items(repository: State<'_, TrashRepository>) -> Result<Vec<TrashedItem>> {
+    let user_id = 1; // TODO: Get from auth
+    let settings = AppSettings::load();
+    let items = repository
+        .get_trashed_items(user_id, settings.trash_retention_days)
+        .await?;
+    Ok(items)
+}
+
+#[tauri::command]
+pub async fn restore_from_trash(
+    item_type: TrashItemType,
+    item_id: i32,
+    repository: State<'_, TrashRepository>,
+) -> Result<bool> {
+    let user_id = 1; // TODO: Get from auth
+    match item_type {
+        TrashItemType::Note => repository.restore_note(item_id, user_id).await?,
+        TrashItemType::Folder => repository.restore_folder(item_id, user_id).await?,
+        TrashItemType::Tag => repository.restore_tag(item_id, user_id).await?,
+    }
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn empty_trash(repository: State<'_, TrashRepository>) -> Result<PurgeSummary> {
+    let user_id = 1; // TODO: Get from auth
+    let summary = repository.empty_trash(user_id).await?;
+    Ok(summary)
+}
+
+#[tauri::command]
+pub async fn purge_note_permanently(
+    note_id: i32,
+    repository: State<'_, TrashRepository>,
+) -> Result<bool> {
+    let user_id = 1; // TODO: Get from auth
+    repository.purge_note(note_id, user_id).await?;
+    Ok(true)
+}
@@ -25,7 +25,6 @@ pub fn get_app_version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
 }
 
-#[allow(dead_code)]
 pub fn get_data_dir() -> String {
     let home = std::env::var("HOME")
         .or_else(|_| std::env::var("USERPROFILE"))
@@ -33,7 +32,6 @@ pub fn get_data_dir() -> String {
     format!("{}/.recall", home)
 }
 
-#[allow(dead_code)]
 pub fn get_attachments_dir() -> String {
     format!("{}/attachments", get_data_dir())
 }
@@ -4,6 +4,7 @@ use std::path::Path;
 
 #[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppSettings {
     pub theme: String,
     pub language: String,
@@ -18,6 +19,18 @@ pub struct AppSettings {
     pub backup_enabled: bool,
     pub backup_interval: u32,
     pub backup_count: u32,
+    // Revisions kept per note; 0 keeps every revision
+    pub revision_retention_count: u32,
+    // Revisions older than this many days are pruned; 0 keeps them forever
+    pub revision_retention_days: u32,
+    // Days an item stays in the trash before it is purged; 0 disables purging
+    pub trash_retention_days: u32,
+    // Top-level folder daily notes are filed in; empty leaves them unfiled
+    pub journal_folder: String,
+    // chrono format used to title daily notes
+    pub journal_title_format: String,
+    // Template new daily notes are created from, if any
+    pub journal_template_id: Option<i32>,
 }
 
 impl Default for AppSettings {
@@ -36,12 +49,17 @@ impl Default for AppSettings {
             backup_enabled: true,
             backup_interval: 24,
             backup_count: 10,
+            revision_retention_count: 100,
+            revision_retention_days: 90,
+            trash_retention_days: 30,
+            journal_folder: "Journal".to_string(),
+            journal_title_format: "%Y-%m-%d".to_string(),
+            journal_template_id: None,
         }
     }
 }
 
 impl AppSettings {
-    #[allow(dead_code)]
     pub fn load() -> Self {
         let settings_path = Self::get_settings_path();
 
@@ -54,7 +72,6 @@ impl AppSettings {
         }
     }
 
-    #[allow(dead_code)]
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
         let settings_path = Self::get_settings_path();
         let settings_dir = Path::new(&settings_path).parent().unwrap();
@@ -68,7 +85,6 @@ impl AppSettings {
         Ok(())
     }
 
-    #[allow(dead_code)]
     fn get_settings_path() -> String {
         if cfg!(debug_assertions) {
             "./settings.json".to_string()
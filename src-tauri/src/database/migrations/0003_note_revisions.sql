@@ -0,0 +1,14 @@
+-- Revision history for notes. Each row is a snapshot of a note's title and
+-- content taken right before an update overwrote them.
+CREATE TABLE note_revisions (
+    revision_id SERIAL PRIMARY KEY,
+    note_id INTEGER NOT NULL REFERENCES notes(note_id) ON DELETE CASCADE,
+    revision_number INTEGER NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    content TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(note_id, revision_number)
+);
+
+CREATE INDEX idx_note_revisions_note_id ON note_revisions(note_id);
+CREATE INDEX idx_note_revisions_created_at ON note_revisions(created_at);
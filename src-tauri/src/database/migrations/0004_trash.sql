@@ -0,0 +1,22 @@
+-- Trash bin: soft deletion for folders and tags, and a deletion timestamp on
+-- every trashable row so expired items can be purged.
+ALTER TABLE notes ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+UPDATE notes SET deleted_at = updated_at WHERE is_deleted = TRUE;
+
+ALTER TABLE folders ADD COLUMN is_deleted BOOLEAN DEFAULT FALSE;
+ALTER TABLE folders ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+
+ALTER TABLE tags ADD COLUMN is_deleted BOOLEAN DEFAULT FALSE;
+ALTER TABLE tags ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+
+-- Trashed folders and tags must not block reusing their names
+ALTER TABLE folders DROP CONSTRAINT folders_user_id_name_parent_folder_id_key;
+CREATE UNIQUE INDEX idx_folders_unique_name ON folders(user_id, name, parent_folder_id)
+    WHERE is_deleted = FALSE;
+
+ALTER TABLE tags DROP CONSTRAINT tags_user_id_name_key;
+CREATE UNIQUE INDEX idx_tags_unique_name ON tags(user_id, name) WHERE is_deleted = FALSE;
+
+CREATE INDEX idx_notes_deleted_at ON notes(deleted_at) WHERE is_deleted = TRUE;
+CREATE INDEX idx_folders_deleted_at ON folders(deleted_at) WHERE is_deleted = TRUE;
+CREATE INDEX idx_tags_deleted_at ON tags(deleted_at) WHERE is_deleted = TRUE;
@@ -0,0 +1,7 @@
+-- Manual ordering and keyset pagination support for note listings
+ALTER TABLE notes ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX idx_notes_user_updated ON notes(user_id, updated_at, note_id) WHERE is_deleted = FALSE;
+CREATE INDEX idx_notes_user_created ON notes(user_id, created_at, note_id) WHERE is_deleted = FALSE;
+CREATE INDEX idx_notes_user_title ON notes(user_id, title, note_id) WHERE is_deleted = FALSE;
+CREATE INDEX idx_notes_user_sort_order ON notes(user_id, sort_order, note_id) WHERE is_deleted = FALSE;
@@ -0,0 +1,17 @@
+-- Wiki-style [[links]] between notes. Each row is one link found in the
+-- source note's content; target_note_id is NULL while no note carries the
+-- linked title.
+CREATE TABLE note_links (
+    link_id SERIAL PRIMARY KEY,
+    source_note_id INTEGER NOT NULL REFERENCES notes(note_id) ON DELETE CASCADE,
+    target_note_id INTEGER REFERENCES notes(note_id) ON DELETE SET NULL,
+    target_title TEXT NOT NULL,
+    alias TEXT,
+    context TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_note_links_source ON note_links(source_note_id);
+CREATE INDEX idx_note_links_target ON note_links(target_note_id);
+CREATE INDEX idx_note_links_target_title ON note_links(LOWER(target_title));
+CREATE INDEX idx_notes_user_lower_title ON notes(user_id, LOWER(title)) WHERE is_deleted = FALSE;
@@ -0,0 +1,17 @@
+-- Reusable note structures. Title and content may contain {{variables}};
+-- prompts holds the template's custom variables as a JSON array.
+CREATE TABLE note_templates (
+    template_id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    title_template VARCHAR(255) NOT NULL DEFAULT '',
+    content TEXT NOT NULL DEFAULT '',
+    default_folder_id INTEGER REFERENCES folders(folder_id) ON DELETE SET NULL,
+    default_tags TEXT[] NOT NULL DEFAULT '{}',
+    prompts JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(user_id, name)
+);
+
+CREATE INDEX idx_note_templates_user_id ON note_templates(user_id);
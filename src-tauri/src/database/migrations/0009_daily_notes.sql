@@ -0,0 +1,5 @@
+-- Daily journal notes: at most one live note per user and calendar day
+ALTER TABLE notes ADD COLUMN journal_date DATE;
+
+CREATE UNIQUE INDEX idx_notes_journal_date ON notes(user_id, journal_date)
+    WHERE journal_date IS NOT NULL AND is_deleted = FALSE;
@@ -0,0 +1,20 @@
+-- Reminders attached to notes. A recurring reminder moves remind_at to its
+-- next occurrence each time it fires; a one-off reminder is marked done.
+-- missed is set when a reminder came due while the app was not running and
+-- stays set until the user dismisses it.
+CREATE TABLE note_reminders (
+    reminder_id SERIAL PRIMARY KEY,
+    note_id INTEGER NOT NULL REFERENCES notes(note_id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    remind_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    recurrence VARCHAR(20) CHECK (recurrence IN ('daily', 'weekly', 'monthly', 'yearly')),
+    message VARCHAR(255),
+    is_done BOOLEAN NOT NULL DEFAULT FALSE,
+    missed BOOLEAN NOT NULL DEFAULT FALSE,
+    last_fired_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_note_reminders_note_id ON note_reminders(note_id);
+CREATE INDEX idx_note_reminders_pending ON note_reminders(remind_at) WHERE is_done = FALSE;
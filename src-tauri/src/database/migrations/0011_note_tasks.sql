@@ -0,0 +1,14 @@
+-- Markdown checkbox items (`- [ ]` / `- [x]`) extracted from note content.
+-- Rebuilt for a note whenever its content is saved.
+CREATE TABLE note_tasks (
+    task_id SERIAL PRIMARY KEY,
+    note_id INTEGER NOT NULL REFERENCES notes(note_id) ON DELETE CASCADE,
+    line_number INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    is_done BOOLEAN NOT NULL DEFAULT FALSE,
+    due_date DATE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_note_tasks_note_id ON note_tasks(note_id);
+CREATE INDEX idx_note_tasks_open ON note_tasks(due_date) WHERE is_done = FALSE;
@@ -0,0 +1,22 @@
+-- Optimistic concurrency for note edits. version goes up by one whenever a
+-- note's title or content changes; revisions remember the version they
+-- were taken from so a stale edit can be merged against its base.
+ALTER TABLE notes ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE note_revisions ADD COLUMN version INTEGER;
+
+CREATE INDEX idx_note_revisions_note_version ON note_revisions(note_id, version);
+
+CREATE OR REPLACE FUNCTION bump_note_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.title IS DISTINCT FROM OLD.title OR NEW.content IS DISTINCT FROM OLD.content THEN
+        NEW.version = OLD.version + 1;
+    ELSE
+        NEW.version = OLD.version;
+    END IF;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER bump_notes_version BEFORE UPDATE ON notes
+    FOR EACH ROW EXECUTE FUNCTION bump_note_version();
@@ -0,0 +1,20 @@
+-- Typed values from the YAML frontmatter at the top of note content.
+-- Rebuilt for a note whenever its content is saved. Exactly one of the
+-- value columns is set, matching `value_type`.
+CREATE TABLE note_properties (
+    property_id SERIAL PRIMARY KEY,
+    note_id INTEGER NOT NULL REFERENCES notes(note_id) ON DELETE CASCADE,
+    key VARCHAR(100) NOT NULL,
+    value_type VARCHAR(20) NOT NULL
+        CHECK (value_type IN ('string', 'number', 'date', 'boolean', 'list')),
+    text_value TEXT,
+    number_value DOUBLE PRECISION,
+    date_value DATE,
+    bool_value BOOLEAN,
+    list_value TEXT[],
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Keys are case-insensitive
+CREATE UNIQUE INDEX idx_note_properties_note_key ON note_properties(note_id, LOWER(key));
+CREATE INDEX idx_note_properties_key ON note_properties(LOWER(key));
@@ -0,0 +1,17 @@
+-- Searches the user runs often: a query in the search box syntax plus the
+-- listing options (filters and sort order) it runs with. Pinned searches
+-- show up in the sidebar as smart folders, ordered by sidebar_order.
+CREATE TABLE saved_searches (
+    saved_search_id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    query TEXT NOT NULL,
+    options JSONB NOT NULL DEFAULT '{}',
+    is_pinned BOOLEAN NOT NULL DEFAULT FALSE,
+    sidebar_order INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(user_id, name)
+);
+
+CREATE INDEX idx_saved_searches_pinned ON saved_searches(user_id, sidebar_order) WHERE is_pinned = TRUE;
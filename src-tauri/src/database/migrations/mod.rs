@@ -29,8 +29,8 @@ pub async fn run_migrations(pool: &Pool<Postgres>) -> Result<(), sqlx::Error> {
     let migration_dir = Path::new(MIGRATIONS_DIR);
     if !migration_dir.exists() {
         fs::create_dir_all(migration_dir)?;
-        create_initial_migrations(migration_dir)?;
     }
+    write_bundled_migrations(migration_dir)?;
 
     let mut migration_files: Vec<_> = fs::read_dir(migration_dir)?
         .filter_map(|entry| {
@@ -57,10 +57,10 @@ pub async fn run_migrations(pool: &Pool<Postgres>) -> Result<(), sqlx::Error> {
 
             let mut transaction = pool.begin().await?;
 
-            // Split SQL by semicolons and execute each statement
-            for statement in migration_sql.split(';').filter(|s| !s.trim().is_empty()) {
-                sqlx::query(statement).execute(&mut *transaction).await?;
-            }
+            // Execute the whole file at once so dollar-quoted function bodies stay intact
+            sqlx::raw_sql(&migration_sql)
+                .execute(&mut *transaction)
+                .await?;
 
             // Record migration
             sqlx::query("INSERT INTO schema_migrations (version) VALUES ($1)")
@@ -77,12 +77,67 @@ pub async fn run_migrations(pool: &Pool<Postgres>) -> Result<(), sqlx::Error> {
     Ok(())
 }
 
-fn create_initial_migrations(dir: &Path) -> std::io::Result<()> {
-    let initial_schema = include_str!("./0001_initial_schema.sql");
-    fs::write(dir.join("0001_initial_schema.sql"), initial_schema)?;
-
-    let add_tags = include_str!("./0002_add_tags.sql");
-    fs::write(dir.join("0002_add_tags.sql"), add_tags)?;
+// Migrations shipped with the binary, written to the migrations directory on startup
+const BUNDLED_MIGRATIONS: &[(&str, &str)] = &[
+    (
+        "0001_initial_schema.sql",
+        include_str!("./0001_initial_schema.sql"),
+    ),
+    ("0002_add_tags.sql", include_str!("./0002_add_tags.sql")),
+    (
+        "0003_note_revisions.sql",
+        include_str!("./0003_note_revisions.sql"),
+    ),
+    ("0004_trash.sql", include_str!("./0004_trash.sql")),
+    (
+        "0005_note_listing.sql",
+        include_str!("./0005_note_listing.sql"),
+    ),
+    (
+        "0006_fuzzy_search.sql",
+        include_str!("./0006_fuzzy_search.sql"),
+    ),
+    (
+        "0007_note_links.sql",
+        include_str!("./0007_note_links.sql"),
+    ),
+    (
+        "0008_note_templates.sql",
+        include_str!("./0008_note_templates.sql"),
+    ),
+    (
+        "0009_daily_notes.sql",
+        include_str!("./0009_daily_notes.sql"),
+    ),
+    (
+        "0010_note_reminders.sql",
+        include_str!("./0010_note_reminders.sql"),
+    ),
+    (
+        "0011_note_tasks.sql",
+        include_str!("./0011_note_tasks.sql"),
+    ),
+    (
+        "0012_note_versions.sql",
+        include_str!("./0012_note_versions.sql"),
+    ),
+    (
+        "0013_note_properties.sql",
+        include_str!("./0013_note_properties.sql"),
+    ),
+    (
+        "0014_saved_searches.sql",
+        include_str!("./0014_saved_searches.sql"),
+    ),
+];
+
+fn write_bundled_migrations(dir: &Path) -> std::io::Result<()> {
+    for (name, sql) in BUNDLED_MIGRATIONS {
+        let path = dir.join(name);
+        if !path.exists() {
+            fs::write(path, sql)?;
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+// Changes applied to every note in a bulk update. Fields left out are not touched.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BulkNoteOperations {
+    // Move to this folder; null moves to the top level
+    #[serde(with = "serde_with::rust::double_option")]
+    pub folder_id: Option<Option<i32>>,
+    pub add_tags: Vec<String>,
+    pub remove_tags: Vec<String>,
+    pub is_archived: Option<bool>,
+    pub is_pinned: Option<bool>,
+    // Move the notes to the trash
+    pub delete: bool,
+}
+
+impl BulkNoteOperations {
+    pub fn is_empty(&self) -> bool {
+        self.folder_id.is_none()
+            && self.add_tags.is_empty()
+            && self.remove_tags.is_empty()
+            && self.is_archived.is_none()
+            && self.is_pinned.is_none()
+            && !self.delete
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkNoteResult {
+    pub note_id: i32,
+    pub success: bool,
+    // Why the note was skipped
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkUpdateSummary {
+    pub results: Vec<BulkNoteResult>,
+    pub succeeded: usize,
+    pub failed: usize,
+}
@@ -1,3 +1,4 @@
+use super::saved_search::SmartFolder;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -17,5 +18,47 @@ pub struct Folder {
 pub struct FolderWithChildren {
     pub folder: Folder,
     pub children: Vec<FolderWithChildren>,
+    // Notes directly in the folder
     pub note_count: i64,
-}
\ No newline at end of file
+    // Notes in the folder and all of its subfolders
+    pub recursive_note_count: i64,
+}
+
+// A folder addressed by path, with the chain of folders from the top level
+// down to it (breadcrumbs)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderPath {
+    // Escaped as described in `utils::folder_path`
+    pub path: String,
+    pub folders: Vec<Folder>,
+}
+
+// What happens to a folder's contents when the folder is deleted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", content = "folder_id", rename_all = "snake_case")]
+pub enum FolderDeleteMode {
+    // Subfolders and notes take the folder's place in its parent
+    MoveContentsToParent,
+    // The folder, its subfolders and all of their notes go to the trash
+    TrashEverything,
+    // Notes from the whole subtree move to the given folder, then the
+    // folders go to the trash
+    MoveNotesTo(i32),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderDeleteSummary {
+    pub folder_id: i32,
+    pub mode: FolderDeleteMode,
+    pub folders_trashed: u64,
+    pub folders_moved: u64,
+    pub notes_trashed: u64,
+    pub notes_moved: u64,
+}
+
+// The sidebar: real folders followed by pinned saved searches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderTree {
+    pub folders: Vec<FolderWithChildren>,
+    pub smart_folders: Vec<SmartFolder>,
+}
@@ -0,0 +1,16 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DailyNoteSummary {
+    pub note_id: i32,
+    pub journal_date: NaiveDate,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdjacentDailyNotes {
+    pub previous: Option<DailyNoteSummary>,
+    pub next: Option<DailyNoteSummary>,
+}
@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+// A note linking to the requested note
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Backlink {
+    pub link_id: i32,
+    pub source_note_id: i32,
+    pub source_title: String,
+    pub alias: Option<String>,
+    pub context: String,
+    pub source_updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OutgoingLink {
+    pub link_id: i32,
+    pub target_note_id: Option<i32>,
+    pub target_title: String,
+    pub alias: Option<String>,
+    pub context: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutgoingLinks {
+    pub resolved: Vec<OutgoingLink>,
+    // Links to titles that no note carries yet
+    pub dangling: Vec<OutgoingLink>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkSource {
+    pub note_id: i32,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkRewriteNote {
+    pub note_id: i32,
+    pub title: String,
+    pub occurrences: usize,
+}
+
+// What renaming a note with `rewrite_links` would change in other notes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkRewritePreview {
+    pub note_id: i32,
+    pub old_title: String,
+    pub new_title: String,
+    pub notes: Vec<LinkRewriteNote>,
+    pub total_occurrences: usize,
+}
+
+// A linked title with no matching note, and the notes that link to it
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UnresolvedLink {
+    pub target_title: String,
+    pub occurrences: i64,
+    #[sqlx(json)]
+    pub sources: Vec<LinkSource>,
+}
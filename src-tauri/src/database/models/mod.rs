@@ -1,16 +1,55 @@
 pub mod attachment;
+pub mod bulk;
 pub mod folder;
+pub mod journal;
+pub mod link;
 pub mod note;
+pub mod note_query;
+pub mod property;
+pub mod related;
+pub mod reminder;
+pub mod revision;
+pub mod saved_search;
+pub mod search;
 pub mod tag;
+pub mod task;
+pub mod template;
+pub mod trash;
 pub mod user;
 
 #[allow(unused_imports)]
 pub use attachment::*;
 #[allow(unused_imports)]
+pub use bulk::*;
+#[allow(unused_imports)]
 pub use folder::*;
 #[allow(unused_imports)]
+pub use journal::*;
+#[allow(unused_imports)]
+pub use link::*;
+#[allow(unused_imports)]
 pub use note::*;
 #[allow(unused_imports)]
+pub use note_query::*;
+#[allow(unused_imports)]
+pub use property::*;
+#[allow(unused_imports)]
+pub use related::*;
+#[allow(unused_imports)]
+pub use reminder::*;
+#[allow(unused_imports)]
+pub use revision::*;
+#[allow(unused_imports)]
+pub use saved_search::*;
+#[allow(unused_imports)]
+pub use search::*;
+#[allow(unused_imports)]
 pub use tag::*;
 #[allow(unused_imports)]
+pub use task::*;
+#[allow(unused_imports)]
+pub use template::*;
+#[allow(unused_imports)]
+pub use trash::*;
+#[allow(unused_imports)]
 pub use user::*;
@@ -1,4 +1,5 @@
-use chrono::{DateTime, Utc};
+use crate::utils::merge::MergeSuggestion;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
@@ -12,6 +13,11 @@ pub struct Note {
     pub is_pinned: bool,
     pub is_archived: bool,
     pub is_deleted: bool,
+    pub sort_order: i32,
+    // Set on daily journal notes: the calendar day the note belongs to
+    pub journal_date: Option<NaiveDate>,
+    // Bumped whenever the title or content changes
+    pub version: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -36,3 +42,15 @@ pub struct TagInfo {
     pub name: String,
     pub color: Option<String>,
 }
+
+// Returned with `AppError::Conflict` when an update was based on an older
+// version of the note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteConflict {
+    // The note as it is stored now
+    pub server: NoteWithRelations,
+    pub expected_version: i32,
+    // The caller's content merged with the stored content, when the version
+    // the caller started from is still in the revision history
+    pub merge: Option<MergeSuggestion>,
+}
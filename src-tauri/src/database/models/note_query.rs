@@ -0,0 +1,167 @@
+use super::note::{Note, NoteWithRelations};
+use crate::utils::error::{AppError, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+pub const MAX_PAGE_SIZE: u32 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteSortField {
+    Created,
+    #[default]
+    Updated,
+    Title,
+    Manual,
+    // Full-text rank; only valid when searching
+    Relevance,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortDirection {
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => "ASC",
+            SortDirection::Desc => "DESC",
+        }
+    }
+
+    // Comparison that selects rows strictly after the cursor
+    pub fn after_operator(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => ">",
+            SortDirection::Desc => "<",
+        }
+    }
+}
+
+// Shared listing options accepted by every note listing command
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NoteQuery {
+    pub cursor: Option<String>,
+    pub page_size: Option<u32>,
+    pub sort: NoteSortField,
+    pub direction: SortDirection,
+    pub folder_id: Option<i32>,
+    // Notes must carry every listed tag
+    pub tag_ids: Vec<i32>,
+    pub is_pinned: Option<bool>,
+    pub is_archived: Option<bool>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub updated_after: Option<DateTime<Utc>>,
+    pub updated_before: Option<DateTime<Utc>>,
+}
+
+impl NoteQuery {
+    pub fn limit(&self) -> u32 {
+        self.page_size
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE)
+    }
+
+    pub fn decode_cursor(&self) -> Result<Option<NoteCursor>> {
+        let Some(cursor) = &self.cursor else {
+            return Ok(None);
+        };
+
+        let cursor = NoteCursor::decode(cursor)?;
+        if cursor.sort != self.sort || cursor.direction != self.direction {
+            return Err(AppError::ValidationError(
+                "Cursor does not match the requested sort order".to_string(),
+            ));
+        }
+
+        Ok(Some(cursor))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum CursorValue {
+    Timestamp(DateTime<Utc>),
+    Text(String),
+    Number(i32),
+    Score(f32),
+}
+
+// Position of the last row on a page: its sort key plus the note id as a tie-breaker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteCursor {
+    pub sort: NoteSortField,
+    pub direction: SortDirection,
+    pub value: CursorValue,
+    pub note_id: i32,
+    // Set when the page came from fuzzy search, so later pages stay fuzzy
+    #[serde(default)]
+    pub fuzzy: bool,
+}
+
+impl NoteCursor {
+    pub fn for_note(
+        note: &Note,
+        score: f32,
+        sort: NoteSortField,
+        direction: SortDirection,
+    ) -> Self {
+        let value = match sort {
+            NoteSortField::Created => CursorValue::Timestamp(note.created_at),
+            NoteSortField::Updated => CursorValue::Timestamp(note.updated_at),
+            NoteSortField::Title => CursorValue::Text(note.title.clone()),
+            NoteSortField::Manual => CursorValue::Number(note.sort_order),
+            NoteSortField::Relevance => CursorValue::Score(score),
+        };
+
+        Self {
+            sort,
+            direction,
+            value,
+            note_id: note.note_id,
+            fuzzy: false,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let invalid = || AppError::ValidationError("Invalid cursor".to_string());
+
+        let json = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        let cursor: NoteCursor = serde_json::from_slice(&json).map_err(|_| invalid())?;
+
+        let matches_sort = matches!(
+            (&cursor.sort, &cursor.value),
+            (NoteSortField::Created, CursorValue::Timestamp(_))
+                | (NoteSortField::Updated, CursorValue::Timestamp(_))
+                | (NoteSortField::Title, CursorValue::Text(_))
+                | (NoteSortField::Manual, CursorValue::Number(_))
+                | (NoteSortField::Relevance, CursorValue::Score(_))
+        );
+        if !matches_sort {
+            return Err(invalid());
+        }
+
+        Ok(cursor)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotePage<T = NoteWithRelations> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    // Counted on the first page only; later pages leave it out
+    pub total_estimate: Option<i64>,
+}
@@ -0,0 +1,77 @@
+use super::note_query::SortDirection;
+use crate::utils::frontmatter::PropertyValue;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::{FromRow, Row};
+
+// A frontmatter property of a note, serialized as
+// `{ "key": "priority", "type": "number", "value": 2 }`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteProperty {
+    pub property_id: i32,
+    pub note_id: i32,
+    pub key: String,
+    #[serde(flatten)]
+    pub value: PropertyValue,
+}
+
+impl<'r> FromRow<'r, PgRow> for NoteProperty {
+    fn from_row(row: &'r PgRow) -> sqlx::Result<Self> {
+        let value_type: String = row.try_get("value_type")?;
+        let missing = || sqlx::Error::ColumnDecode {
+            index: "value_type".to_string(),
+            source: format!("no value stored for a {} property", value_type).into(),
+        };
+
+        let value = match value_type.as_str() {
+            "number" => PropertyValue::Number(
+                row.try_get::<Option<f64>, _>("number_value")?
+                    .ok_or_else(missing)?,
+            ),
+            "date" => PropertyValue::Date(
+                row.try_get::<Option<_>, _>("date_value")?
+                    .ok_or_else(missing)?,
+            ),
+            "boolean" => PropertyValue::Boolean(
+                row.try_get::<Option<bool>, _>("bool_value")?
+                    .ok_or_else(missing)?,
+            ),
+            "list" => PropertyValue::List(
+                row.try_get::<Option<Vec<String>>, _>("list_value")?
+                    .ok_or_else(missing)?,
+            ),
+            _ => PropertyValue::String(
+                row.try_get::<Option<String>, _>("text_value")?
+                    .ok_or_else(missing)?,
+            ),
+        };
+
+        Ok(Self {
+            property_id: row.try_get("property_id")?,
+            note_id: row.try_get("note_id")?,
+            key: row.try_get("key")?,
+            value,
+        })
+    }
+}
+
+// A property key in use across the user's notes
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PropertyKey {
+    pub key: String,
+    // Every value type the key has been given, e.g. ["date", "string"]
+    pub value_types: Vec<String>,
+    pub note_count: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PropertyNoteQuery {
+    // See `utils::property_query`, e.g. `status = draft AND priority >= 2`
+    pub filter: Option<String>,
+    // Property key to sort by; notes without it come last
+    pub sort_by: Option<String>,
+    // Ascending unless given
+    pub direction: Option<SortDirection>,
+    pub limit: Option<u32>,
+}
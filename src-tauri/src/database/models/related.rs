@@ -0,0 +1,17 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedNote {
+    pub note_id: i32,
+    pub title: String,
+    pub folder_id: Option<i32>,
+    pub updated_at: DateTime<Utc>,
+    // BM25 text similarity, boosted by shared tags and folder; only
+    // comparable between results for the same note
+    pub score: f32,
+    // The words both notes use that contributed most to the score
+    pub shared_terms: Vec<String>,
+    pub shared_tags: Vec<String>,
+    pub same_folder: bool,
+}
@@ -0,0 +1,74 @@
+use crate::utils::reminder_time::to_local;
+use chrono::{DateTime, Duration, Local, Months, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum Recurrence {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Recurrence {
+    // First occurrence after `now` in the series starting at `from`. Steps are
+    // taken in local time so a daily 9am reminder stays at 9am across DST.
+    pub fn next_after(self, from: DateTime<Utc>, now: DateTime<Utc>) -> DateTime<Utc> {
+        let start = from.with_timezone(&Local).naive_local();
+
+        for step in 1u32.. {
+            let Some(next) = self.advance(start, step) else {
+                break;
+            };
+            let next = to_local(next).with_timezone(&Utc);
+            if next > now {
+                return next;
+            }
+        }
+
+        // Only reachable past the end of chrono's calendar
+        now
+    }
+
+    fn advance(self, start: NaiveDateTime, step: u32) -> Option<NaiveDateTime> {
+        match self {
+            Recurrence::Daily => start.checked_add_signed(Duration::days(step.into())),
+            Recurrence::Weekly => start.checked_add_signed(Duration::weeks(step.into())),
+            Recurrence::Monthly => start.checked_add_months(Months::new(step)),
+            Recurrence::Yearly => start.checked_add_months(Months::new(step.checked_mul(12)?)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NoteReminder {
+    pub reminder_id: i32,
+    pub note_id: i32,
+    pub user_id: i32,
+    // Next time the reminder fires
+    pub remind_at: DateTime<Utc>,
+    pub recurrence: Option<Recurrence>,
+    pub message: Option<String>,
+    pub is_done: bool,
+    // Came due while the app was closed and has not been dismissed yet
+    pub missed: bool,
+    pub last_fired_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// Payload of the `reminder:due` and `reminder:missed` events
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DueReminder {
+    pub reminder_id: i32,
+    pub note_id: i32,
+    pub note_title: String,
+    pub message: Option<String>,
+    // The occurrence that fired
+    pub remind_at: DateTime<Utc>,
+    pub recurrence: Option<Recurrence>,
+    pub missed: bool,
+}
@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NoteRevision {
+    pub revision_id: i32,
+    pub note_id: i32,
+    pub revision_number: i32,
+    pub title: String,
+    pub content: String,
+    // Version of the note this snapshot was taken from; unset for older revisions
+    pub version: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NoteRevisionSummary {
+    pub revision_id: i32,
+    pub note_id: i32,
+    pub revision_number: i32,
+    pub title: String,
+    pub content_length: i32,
+    pub created_at: DateTime<Utc>,
+}
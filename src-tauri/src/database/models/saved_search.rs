@@ -0,0 +1,28 @@
+use super::note_query::NoteQuery;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SavedSearch {
+    pub saved_search_id: i32,
+    pub user_id: i32,
+    pub name: String,
+    // Search box syntax, e.g. `tag:work is:pinned meeting`
+    pub query: String,
+    // Filters and sort order the query runs with; never holds a cursor or page size
+    #[sqlx(json)]
+    pub options: NoteQuery,
+    // Shown in the sidebar as a smart folder
+    pub is_pinned: bool,
+    pub sidebar_order: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// A pinned saved search together with the number of notes it matches right now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmartFolder {
+    pub search: SavedSearch,
+    pub note_count: i64,
+}
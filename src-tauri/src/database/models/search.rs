@@ -0,0 +1,86 @@
+use super::note::{Note, NoteWithRelations};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+// Markers passed to ts_headline. Control characters never show up in note
+// text, so they can be stripped safely when building highlight offsets.
+pub const HIGHLIGHT_START: char = '\u{2}';
+pub const HIGHLIGHT_STOP: char = '\u{3}';
+
+pub const TITLE_HEADLINE_OPTIONS: &str = "StartSel=\u{2}, StopSel=\u{3}, HighlightAll=true";
+pub const CONTENT_HEADLINE_OPTIONS: &str = "StartSel=\u{2}, StopSel=\u{3}, MaxFragments=3, MinWords=10, MaxWords=30, FragmentDelimiter=\" … \"";
+
+#[derive(Debug, Clone, FromRow)]
+pub struct RankedNote {
+    #[sqlx(flatten)]
+    pub note: Note,
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct NoteHeadline {
+    pub note_id: i32,
+    pub title_headline: String,
+    pub content_headline: String,
+}
+
+// Offsets are UTF-16 code units into `Snippet::text`, matching JavaScript string indexing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HighlightRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Snippet {
+    pub text: String,
+    pub highlights: Vec<HighlightRange>,
+}
+
+impl Snippet {
+    // Strip the ts_headline markers and record where each highlighted run sits
+    pub fn from_headline(headline: &str) -> Self {
+        let mut text = String::with_capacity(headline.len());
+        let mut highlights = Vec::new();
+        let mut offset = 0;
+        let mut start = None;
+
+        for c in headline.chars() {
+            match c {
+                HIGHLIGHT_START => start = Some(offset),
+                HIGHLIGHT_STOP => {
+                    if let Some(start) = start.take() {
+                        if offset > start {
+                            highlights.push(HighlightRange { start, end: offset });
+                        }
+                    }
+                }
+                _ => {
+                    text.push(c);
+                    offset += c.len_utf16();
+                }
+            }
+        }
+
+        Self { text, highlights }
+    }
+}
+
+// How a search matched: ranked full-text search, or trigram similarity that
+// tolerates misspellings and partial words
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMode {
+    #[default]
+    FullText,
+    Fuzzy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub note: NoteWithRelations,
+    pub mode: SearchMode,
+    pub score: f32,
+    pub title: Snippet,
+    pub content: Snippet,
+}
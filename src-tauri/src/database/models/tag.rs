@@ -15,4 +15,20 @@ pub struct Tag {
 pub struct TagWithNotes {
     pub tag: Tag,
     pub note_count: i64,
-}
\ No newline at end of file
+}
+
+// An existing tag proposed for a note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagSuggestion {
+    pub tag_id: i32,
+    pub name: String,
+    pub color: Option<String>,
+    // 0..1, combined from the reasons below
+    pub confidence: f32,
+    // Words of the tag's name found in the note
+    pub matched_terms: Vec<String>,
+    // Tags of the note (or named in it) that this tag often appears with
+    pub co_occurring_tags: Vec<String>,
+    // How many of the most similar notes carry this tag
+    pub similar_notes: u32,
+}
@@ -0,0 +1,38 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+// A Markdown checkbox item found in a note's content
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NoteTask {
+    pub task_id: i32,
+    pub note_id: i32,
+    // 1-based line number in the note content
+    pub line_number: i32,
+    pub text: String,
+    pub is_done: bool,
+    pub due_date: Option<NaiveDate>,
+    pub created_at: DateTime<Utc>,
+}
+
+// An unchecked task together with the note it lives in
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OpenTask {
+    pub task_id: i32,
+    pub note_id: i32,
+    pub note_title: String,
+    pub folder_id: Option<i32>,
+    pub line_number: i32,
+    pub text: String,
+    pub due_date: Option<NaiveDate>,
+    pub note_updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TaskFilter {
+    // Only tasks in this folder or its subfolders
+    pub folder_id: Option<i32>,
+    // Only tasks in notes carrying this tag, case-insensitive
+    pub tag: Option<String>,
+}
@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+// A custom variable the user fills in when creating a note from a template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplatePrompt {
+    // Referenced as `{{name}}` in the title or content
+    pub name: String,
+    pub label: String,
+    #[serde(default)]
+    pub default_value: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NoteTemplate {
+    pub template_id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub title_template: String,
+    pub content: String,
+    pub default_folder_id: Option<i32>,
+    pub default_tags: Vec<String>,
+    #[sqlx(json)]
+    pub prompts: Vec<TemplatePrompt>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
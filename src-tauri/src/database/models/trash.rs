@@ -0,0 +1,38 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashItemType {
+    Note,
+    Folder,
+    Tag,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedItem {
+    pub item_type: TrashItemType,
+    pub item_id: i32,
+    pub name: String,
+    // Folder the item lived in (for notes) or its parent folder (for folders)
+    pub folder_id: Option<i32>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    // When the item will be purged automatically, if a retention period is set
+    pub purge_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct TrashedRow {
+    pub item_id: i32,
+    pub name: String,
+    pub folder_id: Option<i32>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PurgeSummary {
+    pub notes: u64,
+    pub folders: u64,
+    pub tags: u64,
+}
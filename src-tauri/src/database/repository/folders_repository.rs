@@ -1,8 +1,21 @@
-use super::super::models::folder::{Folder, FolderWithChildren};
-use crate::utils::error::Result;
-use async_recursion::async_recursion;
-use chrono::Utc;
-use sqlx::{Pool, Postgres};
+use super::super::models::folder::{
+    Folder, FolderDeleteMode, FolderDeleteSummary, FolderPath, FolderWithChildren,
+};
+use super::super::models::note::Note;
+use super::links_repository::LinkRepository;
+use super::notes_repository::NoteRepository;
+use super::trash_repository::remove_files;
+use crate::utils::error::{AppError, Result};
+use crate::utils::folder_path::{format_folder_path, parse_folder_path};
+use crate::utils::helpers::copy_name;
+use sqlx::{Pool, Postgres, QueryBuilder, Transaction};
+use std::collections::{HashMap, HashSet};
+
+// Levels of folders, counting the top level as one
+pub const MAX_FOLDER_DEPTH: i32 = 16;
+
+// Length of the `folders.name` column
+const MAX_FOLDER_NAME_LENGTH: usize = 100;
 
 #[derive(Debug, Clone)]
 pub struct FolderRepository {
@@ -16,6 +29,23 @@ impl FolderRepository {
     }
 
     pub async fn create_folder(&self, dto: CreateFolderDto) -> Result<FolderWithChildren> {
+        let mut tx = self.pool.begin().await?;
+
+        // A new subfolder gets the parent checks of `move_folder`, under the
+        // same lock so the parent cannot be moved deeper in the meantime
+        if let Some(parent_folder_id) = dto.parent_folder_id {
+            lock_folder_moves(&mut tx, dto.user_id).await?;
+            ensure_target_folder(&mut tx, parent_folder_id, dto.user_id).await?;
+
+            let parent_depth = folder_ancestors(&mut tx, parent_folder_id).await?.len() as i32;
+            if parent_depth + 1 > MAX_FOLDER_DEPTH {
+                return Err(AppError::ValidationError(format!(
+                    "Folders cannot be nested more than {} levels deep",
+                    MAX_FOLDER_DEPTH
+                )));
+            }
+        }
+
         let folder = sqlx::query_as::<_, Folder>(
             r#"
             INSERT INTO folders (user_id, name, parent_folder_id, color)
@@ -27,17 +57,20 @@ impl FolderRepository {
         .bind(&dto.name)
         .bind(dto.parent_folder_id)
         .bind(&dto.color)
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
 
+        tx.commit().await?;
         self.get_folder_with_children(folder.folder_id).await
     }
 
     pub async fn get_folder_by_id(&self, folder_id: i32) -> Result<Option<FolderWithChildren>> {
-        let folder = sqlx::query_as::<_, Folder>("SELECT * FROM folders WHERE folder_id = $1")
-            .bind(folder_id)
-            .fetch_optional(&self.pool)
-            .await?;
+        let folder = sqlx::query_as::<_, Folder>(
+            "SELECT * FROM folders WHERE folder_id = $1 AND is_deleted = FALSE",
+        )
+        .bind(folder_id)
+        .fetch_optional(&self.pool)
+        .await?;
 
         if let Some(folder) = folder {
             self.get_folder_with_children(folder.folder_id)
@@ -48,63 +81,149 @@ impl FolderRepository {
         }
     }
 
-    pub async fn get_user_folders(&self, user_id: i32) -> Result<Vec<FolderWithChildren>> {
-        let folders =
-            sqlx::query_as::<_, Folder>("SELECT * FROM folders WHERE user_id = $1 ORDER BY name")
+    // Just the folder row, without loading its subtree
+    pub async fn find_user_folder(&self, folder_id: i32, user_id: i32) -> Result<Option<Folder>> {
+        let folder = sqlx::query_as::<_, Folder>(
+            "SELECT * FROM folders WHERE folder_id = $1 AND user_id = $2 AND is_deleted = FALSE",
+        )
+        .bind(folder_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(folder)
+    }
+
+    // Top-level folder with the given name, created if the user has none
+    pub async fn find_or_create_root_folder(&self, user_id: i32, name: &str) -> Result<Folder> {
+        let mut tx = self.pool.begin().await?;
+        lock_folder_paths(&mut tx, user_id).await?;
+
+        let existing = sqlx::query_as::<_, Folder>(
+            r#"
+            SELECT * FROM folders
+            WHERE user_id = $1 AND parent_folder_id IS NULL AND name = $2 AND is_deleted = FALSE
+            ORDER BY folder_id
+            LIMIT 1
+            "#,
+        )
+        .bind(user_id)
+        .bind(name)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let folder = match existing {
+            Some(folder) => folder,
+            None => {
+                sqlx::query_as::<_, Folder>(
+                    "INSERT INTO folders (user_id, name) VALUES ($1, $2) RETURNING *",
+                )
                 .bind(user_id)
-                .fetch_all(&self.pool)
-                .await?;
+                .bind(name)
+                .fetch_one(&mut *tx)
+                .await?
+            }
+        };
 
-        let mut result = Vec::new();
-        for folder in folders {
-            let with_children = self.get_folder_with_children(folder.folder_id).await?;
-            result.push(with_children);
-        }
+        tx.commit().await?;
+        Ok(folder)
+    }
 
-        Ok(result)
+    // Every folder of the user, each with its own subtree
+    pub async fn get_user_folders(&self, user_id: i32) -> Result<Vec<FolderWithChildren>> {
+        let folders = sqlx::query_as::<_, Folder>(
+            "SELECT * FROM folders WHERE user_id = $1 AND is_deleted = FALSE ORDER BY name",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let roots: Vec<i32> = folders.iter().map(|folder| folder.folder_id).collect();
+        let note_counts = self.count_notes(&roots).await?;
+        Ok(assemble_trees(&folders, &roots, &note_counts))
     }
 
     pub async fn update_folder(&self, dto: UpdateFolderDto) -> Result<FolderWithChildren> {
-        // Build update query dynamically
-        let mut query = "UPDATE folders SET updated_at = $1".to_string();
-        let mut params: Vec<String> = vec![Utc::now().to_string()];
-        let mut param_count = 2;
+        let mut tx = self.pool.begin().await?;
 
-        if let Some(name) = &dto.name {
-            query.push_str(&format!(", name = ${}", param_count));
-            params.push(name.clone());
-            param_count += 1;
+        if dto.parent_folder_id.is_some() {
+            let owner: Option<i32> =
+                sqlx::query_scalar("SELECT user_id FROM folders WHERE folder_id = $1")
+                    .bind(dto.folder_id)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+            if let Some(user_id) = owner {
+                lock_folder_moves(&mut tx, user_id).await?;
+            }
         }
 
+        let current = sqlx::query_as::<_, Folder>(
+            "SELECT * FROM folders WHERE folder_id = $1 AND is_deleted = FALSE FOR UPDATE",
+        )
+        .bind(dto.folder_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Folder not found".to_string()))?;
+
+        // Re-parenting gets the same checks as `move_folder`, with the name
+        // the folder will have once renamed
+        let name = dto.name.as_deref().unwrap_or(&current.name);
         if let Some(parent_folder_id) = dto.parent_folder_id {
-            query.push_str(&format!(", parent_folder_id = ${}", param_count));
-            params.push(
-                parent_folder_id
-                    .map(|id| id.to_string())
-                    .unwrap_or("NULL".to_string()),
-            );
-            param_count += 1;
+            reparent(&mut tx, &current, parent_folder_id, name).await?;
+        } else if dto.name.is_some() {
+            ensure_name_free(&mut tx, &current, current.parent_folder_id, name).await?;
         }
 
+        let mut query = QueryBuilder::<Postgres>::new("UPDATE folders SET updated_at = NOW()");
+        if let Some(name) = &dto.name {
+            query.push(", name = ").push_bind(name);
+        }
         if let Some(color) = &dto.color {
-            query.push_str(&format!(", color = ${}", param_count));
-            params.push(color.clone());
-            param_count += 1;
+            query.push(", color = ").push_bind(color);
         }
+        query
+            .push(" WHERE folder_id = ")
+            .push_bind(dto.folder_id)
+            .push(" RETURNING *");
 
-        query.push_str(&format!(" WHERE folder_id = ${} RETURNING *", param_count));
-        params.push(dto.folder_id.to_string());
+        query
+            .build_query_as::<Folder>()
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| folder_name_taken(e, name))?;
 
-        // Execute update
-        let mut query_builder = sqlx::query_as::<_, Folder>(&query);
+        tx.commit().await?;
+        self.get_folder_with_children(dto.folder_id).await
+    }
 
-        for param in &params {
-            query_builder = query_builder.bind(param);
-        }
+    // Put a folder under another of the user's folders, or at the top level
+    // when `parent_folder_id` is None
+    pub async fn move_folder(
+        &self,
+        folder_id: i32,
+        user_id: i32,
+        parent_folder_id: Option<i32>,
+    ) -> Result<FolderWithChildren> {
+        let mut tx = self.pool.begin().await?;
+        lock_folder_moves(&mut tx, user_id).await?;
 
-        let folder = query_builder.fetch_one(&self.pool).await?;
+        let folder = sqlx::query_as::<_, Folder>(
+            r#"
+            SELECT * FROM folders
+            WHERE folder_id = $1 AND user_id = $2 AND is_deleted = FALSE
+            FOR UPDATE
+            "#,
+        )
+        .bind(folder_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Folder not found".to_string()))?;
 
-        self.get_folder_with_children(folder.folder_id).await
+        reparent(&mut tx, &folder, parent_folder_id, &folder.name).await?;
+
+        tx.commit().await?;
+        self.get_folder_with_children(folder_id).await
     }
 
     pub async fn delete_folder(&self, folder_id: i32, user_id: i32) -> Result<()> {
@@ -118,7 +237,6 @@ impl FolderRepository {
         .await?;
 
         if note_count > 0 {
-            use crate::utils::error::AppError;
             return Err(AppError::InvalidInput(
                 "Cannot delete folder that contains notes".to_string(),
             ));
@@ -126,7 +244,7 @@ impl FolderRepository {
 
         // Check if folder has subfolders
         let subfolder_count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM folders WHERE parent_folder_id = $1 AND user_id = $2",
+            "SELECT COUNT(*) FROM folders WHERE parent_folder_id = $1 AND user_id = $2 AND is_deleted = FALSE",
         )
         .bind(folder_id)
         .bind(user_id)
@@ -134,100 +252,828 @@ impl FolderRepository {
         .await?;
 
         if subfolder_count > 0 {
-            use crate::utils::error::AppError;
             return Err(AppError::InvalidInput(
                 "Cannot delete folder that contains subfolders".to_string(),
             ));
         }
 
-        sqlx::query("DELETE FROM folders WHERE folder_id = $1 AND user_id = $2")
-            .bind(folder_id)
-            .bind(user_id)
-            .execute(&self.pool)
-            .await?;
+        // Move the folder to the trash; it keeps its parent so a restore puts it back in place
+        sqlx::query(
+            "UPDATE folders SET is_deleted = TRUE, deleted_at = NOW() WHERE folder_id = $1 AND user_id = $2 AND is_deleted = FALSE"
+        )
+        .bind(folder_id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
 
         Ok(())
     }
 
-    pub async fn get_folder_tree(&self, user_id: i32) -> Result<Vec<FolderWithChildren>> {
-        let root_folders = sqlx::query_as::<_, Folder>(
-            "SELECT * FROM folders WHERE user_id = $1 AND parent_folder_id IS NULL ORDER BY name",
+    // Delete a folder that may still hold notes and subfolders, handling its
+    // contents according to `mode`, all in one transaction
+    pub async fn delete_folder_with_contents(
+        &self,
+        folder_id: i32,
+        user_id: i32,
+        mode: FolderDeleteMode,
+    ) -> Result<FolderDeleteSummary> {
+        let mut tx = self.pool.begin().await?;
+        // Children may be re-parented, and a folder moved into the subtree
+        // meanwhile would be left live under a trashed parent
+        lock_folder_moves(&mut tx, user_id).await?;
+
+        let folder = sqlx::query_as::<_, Folder>(
+            r#"
+            SELECT * FROM folders
+            WHERE folder_id = $1 AND user_id = $2 AND is_deleted = FALSE
+            FOR UPDATE
+            "#,
         )
+        .bind(folder_id)
         .bind(user_id)
-        .fetch_all(&self.pool)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Folder not found".to_string()))?;
+
+        let subtree: Vec<i32> = sqlx::query_scalar(
+            r#"
+            WITH RECURSIVE subtree AS (
+                SELECT folder_id FROM folders WHERE folder_id = $1
+                UNION
+                SELECT f.folder_id FROM folders f
+                JOIN subtree s ON f.parent_folder_id = s.folder_id
+                WHERE f.is_deleted = FALSE
+            )
+            SELECT folder_id FROM subtree
+            "#,
+        )
+        .bind(folder_id)
+        .fetch_all(&mut *tx)
         .await?;
 
-        let mut tree = Vec::new();
-        for folder in root_folders {
-            let with_children = self.build_folder_tree(&folder).await?;
-            tree.push(with_children);
+        let mut summary = FolderDeleteSummary {
+            folder_id,
+            mode,
+            folders_trashed: 0,
+            folders_moved: 0,
+            notes_trashed: 0,
+            notes_moved: 0,
+        };
+
+        match mode {
+            FolderDeleteMode::MoveContentsToParent => {
+                let clash: Option<String> = sqlx::query_scalar(
+                    r#"
+                    SELECT child.name FROM folders child
+                    JOIN folders sibling ON sibling.user_id = child.user_id
+                        AND sibling.parent_folder_id IS NOT DISTINCT FROM $2
+                        AND sibling.name = child.name
+                        AND sibling.folder_id <> $1
+                        AND sibling.is_deleted = FALSE
+                    WHERE child.parent_folder_id = $1 AND child.is_deleted = FALSE
+                    ORDER BY child.name
+                    LIMIT 1
+                    "#,
+                )
+                .bind(folder_id)
+                .bind(folder.parent_folder_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+                if let Some(name) = clash {
+                    return Err(name_conflict(&name));
+                }
+
+                summary.folders_moved = sqlx::query(
+                    r#"
+                    UPDATE folders SET parent_folder_id = $1, updated_at = NOW()
+                    WHERE parent_folder_id = $2 AND is_deleted = FALSE
+                    "#,
+                )
+                .bind(folder.parent_folder_id)
+                .bind(folder_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+
+                summary.notes_moved =
+                    move_notes(&mut tx, &[folder_id], folder.parent_folder_id).await?;
+                summary.folders_trashed = trash_folders(&mut tx, &[folder_id]).await?;
+            }
+            FolderDeleteMode::TrashEverything => {
+                let titles: Vec<String> = sqlx::query_scalar(
+                    r#"
+                    UPDATE notes SET is_deleted = TRUE, deleted_at = NOW()
+                    WHERE folder_id = ANY($1) AND is_deleted = FALSE
+                    RETURNING title
+                    "#,
+                )
+                .bind(&subtree)
+                .fetch_all(&mut *tx)
+                .await?;
+
+                // Links to trashed notes dangle until they are restored
+                if !titles.is_empty() {
+                    LinkRepository::resolve_titles(&mut tx, user_id, &titles).await?;
+                }
+
+                summary.notes_trashed = titles.len() as u64;
+                summary.folders_trashed = trash_folders(&mut tx, &subtree).await?;
+            }
+            FolderDeleteMode::MoveNotesTo(target_folder_id) => {
+                if subtree.contains(&target_folder_id) {
+                    return Err(AppError::InvalidInput(
+                        "Notes cannot be moved into a folder that is being deleted".to_string(),
+                    ));
+                }
+                let target_exists: bool = sqlx::query_scalar(
+                    "SELECT EXISTS(SELECT 1 FROM folders WHERE folder_id = $1 AND user_id = $2 AND is_deleted = FALSE)",
+                )
+                .bind(target_folder_id)
+                .bind(user_id)
+                .fetch_one(&mut *tx)
+                .await?;
+                if !target_exists {
+                    return Err(AppError::NotFound("Target folder not found".to_string()));
+                }
+
+                summary.notes_moved = move_notes(&mut tx, &subtree, Some(target_folder_id)).await?;
+                summary.folders_trashed = trash_folders(&mut tx, &subtree).await?;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(summary)
+    }
+
+    // The folders named by `path`; NotFound names the first missing part
+    pub async fn resolve_folder_path(&self, user_id: i32, path: &str) -> Result<FolderPath> {
+        let names = parse_folder_path(path)?;
+        let mut tx = self.pool.begin().await?;
+
+        let mut folders: Vec<Folder> = Vec::with_capacity(names.len());
+        for (i, name) in names.iter().enumerate() {
+            let parent_folder_id = folders.last().map(|folder| folder.folder_id);
+            let folder = find_child(&mut tx, user_id, parent_folder_id, name)
+                .await?
+                .ok_or_else(|| {
+                    AppError::NotFound(format!(
+                        "Folder not found: {}",
+                        format_folder_path(&names[..=i])
+                    ))
+                })?;
+            folders.push(folder);
+        }
+
+        tx.commit().await?;
+        Ok(to_folder_path(folders))
+    }
+
+    // Like `mkdir -p`: the folders named by `path`, creating whichever are
+    // missing. Either the whole path exists afterwards or nothing is created.
+    pub async fn ensure_folder_path(&self, user_id: i32, path: &str) -> Result<FolderPath> {
+        let names = parse_folder_path(path)?;
+        if names.len() > MAX_FOLDER_DEPTH as usize {
+            return Err(AppError::ValidationError(format!(
+                "Folders cannot be nested more than {} levels deep",
+                MAX_FOLDER_DEPTH
+            )));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        lock_folder_paths(&mut tx, user_id).await?;
+
+        let mut folders: Vec<Folder> = Vec::with_capacity(names.len());
+        for name in &names {
+            let parent_folder_id = folders.last().map(|folder| folder.folder_id);
+            let folder = match find_child(&mut tx, user_id, parent_folder_id, name).await? {
+                Some(folder) => folder,
+                None => sqlx::query_as::<_, Folder>(
+                    r#"
+                    INSERT INTO folders (user_id, name, parent_folder_id)
+                    VALUES ($1, $2, $3)
+                    RETURNING *
+                    "#,
+                )
+                .bind(user_id)
+                .bind(name)
+                .bind(parent_folder_id)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|e| folder_name_taken(e, name))?,
+            };
+            folders.push(folder);
         }
 
-        Ok(tree)
+        tx.commit().await?;
+        Ok(to_folder_path(folders))
     }
 
-    #[async_recursion]
-    async fn build_folder_tree(&self, parent: &Folder) -> Result<FolderWithChildren> {
-        let children = sqlx::query_as::<_, Folder>(
-            "SELECT * FROM folders WHERE parent_folder_id = $1 ORDER BY name",
+    // Breadcrumbs for a folder, from its top-level ancestor down to itself
+    pub async fn get_folder_path(&self, folder_id: i32, user_id: i32) -> Result<FolderPath> {
+        let folders = sqlx::query_as::<_, Folder>(
+            r#"
+            WITH RECURSIVE ancestors AS (
+                SELECT folders.*, 1 AS depth FROM folders
+                WHERE folder_id = $1 AND user_id = $2 AND is_deleted = FALSE
+                UNION ALL
+                SELECT f.*, a.depth + 1 FROM folders f
+                JOIN ancestors a ON f.folder_id = a.parent_folder_id
+                WHERE a.depth <= $3
+            )
+            SELECT * FROM ancestors ORDER BY depth DESC
+            "#,
         )
-        .bind(parent.folder_id)
+        .bind(folder_id)
+        .bind(user_id)
+        .bind(MAX_FOLDER_DEPTH)
         .fetch_all(&self.pool)
         .await?;
 
-        let mut child_trees = Vec::new();
-        for child in children {
-            let child_tree = self.build_folder_tree(&child).await?;
-            child_trees.push(child_tree);
+        if folders.is_empty() {
+            return Err(AppError::NotFound("Folder not found".to_string()));
         }
 
-        let note_count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM notes WHERE folder_id = $1 AND is_deleted = FALSE",
+        Ok(to_folder_path(folders))
+    }
+
+    // Deep-copy a folder with its subfolders and their notes under
+    // `new_parent_id` (the top level when None). If the destination already
+    // has a folder of that name, the copy is called "Name (copy)".
+    pub async fn copy_folder(
+        &self,
+        folder_id: i32,
+        user_id: i32,
+        new_parent_id: Option<i32>,
+        copy_attachments: bool,
+        notes: &NoteRepository,
+    ) -> Result<FolderWithChildren> {
+        let mut tx = self.pool.begin().await?;
+        // The depth check below only holds while neither the source subtree
+        // nor the new parent is moved
+        lock_folder_moves(&mut tx, user_id).await?;
+
+        let source = sqlx::query_as::<_, Folder>(
+            "SELECT * FROM folders WHERE folder_id = $1 AND user_id = $2 AND is_deleted = FALSE",
         )
-        .bind(parent.folder_id)
-        .fetch_one(&self.pool)
+        .bind(folder_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Folder not found".to_string()))?;
+
+        let parent_depth = match new_parent_id {
+            None => 0,
+            Some(new_parent_id) => {
+                ensure_target_folder(&mut tx, new_parent_id, user_id).await?;
+                folder_ancestors(&mut tx, new_parent_id).await?.len() as i32
+            }
+        };
+        ensure_depth_allowed(&mut tx, folder_id, parent_depth).await?;
+
+        // Read the whole subtree before inserting, so copying a folder into
+        // its own subtree does not copy the copy; parents come before children
+        let subtree = sqlx::query_as::<_, Folder>(
+            r#"
+            WITH RECURSIVE subtree AS (
+                SELECT folders.*, 1 AS depth FROM folders WHERE folder_id = $1
+                UNION ALL
+                SELECT f.*, s.depth + 1 FROM folders f
+                JOIN subtree s ON f.parent_folder_id = s.folder_id
+                WHERE f.is_deleted = FALSE AND s.depth <= $2
+            )
+            SELECT * FROM subtree ORDER BY depth, name
+            "#,
+        )
+        .bind(folder_id)
+        .bind(MAX_FOLDER_DEPTH)
+        .fetch_all(&mut *tx)
         .await?;
 
-        Ok(FolderWithChildren {
-            folder: parent.clone(),
-            children: child_trees,
-            note_count,
-        })
+        // Skip names that differ from a sibling's only in case, too
+        let mut name = source.name.clone();
+        let mut attempt = 0;
+        while find_child(&mut tx, user_id, new_parent_id, &name)
+            .await?
+            .is_some()
+        {
+            attempt += 1;
+            name = copy_name(&source.name, attempt, MAX_FOLDER_NAME_LENGTH);
+        }
+
+        // Old folder id -> id of its copy
+        let mut copies: HashMap<i32, i32> = HashMap::new();
+        for folder in &subtree {
+            if copies.contains_key(&folder.folder_id) {
+                continue;
+            }
+            let (name, parent_folder_id) = if folder.folder_id == folder_id {
+                (name.as_str(), new_parent_id)
+            } else {
+                match folder.parent_folder_id.and_then(|id| copies.get(&id)) {
+                    Some(parent_id) => (folder.name.as_str(), Some(*parent_id)),
+                    None => continue,
+                }
+            };
+
+            let copy_id: i32 = sqlx::query_scalar(
+                r#"
+                INSERT INTO folders (user_id, name, parent_folder_id, color)
+                VALUES ($1, $2, $3, $4)
+                RETURNING folder_id
+                "#,
+            )
+            .bind(user_id)
+            .bind(name)
+            .bind(parent_folder_id)
+            .bind(&folder.color)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| folder_name_taken(e, name))?;
+            copies.insert(folder.folder_id, copy_id);
+        }
+
+        let source_ids: Vec<i32> = copies.keys().copied().collect();
+        let source_notes = sqlx::query_as::<_, Note>(
+            r#"
+            SELECT * FROM notes
+            WHERE folder_id = ANY($1) AND is_deleted = FALSE
+            ORDER BY note_id
+            "#,
+        )
+        .bind(&source_ids)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut copied_files = Vec::new();
+        let copied = async {
+            for note in &source_notes {
+                let folder_id = note.folder_id.and_then(|id| copies.get(&id)).copied();
+                notes
+                    .copy_note(
+                        &mut tx,
+                        note,
+                        folder_id,
+                        note.title.clone(),
+                        copy_attachments,
+                        &mut copied_files,
+                    )
+                    .await?;
+            }
+            tx.commit().await?;
+            Ok::<_, AppError>(())
+        }
+        .await;
+
+        if let Err(e) = copied {
+            remove_files(&copied_files).await;
+            return Err(e);
+        }
+
+        self.get_folder_with_children(copies[&folder_id]).await
+    }
+
+    pub async fn get_folder_tree(&self, user_id: i32) -> Result<Vec<FolderWithChildren>> {
+        self.load_subtrees(SubtreeRoots::TopLevel(user_id)).await
     }
 
-    #[async_recursion]
     async fn get_folder_with_children(&self, folder_id: i32) -> Result<FolderWithChildren> {
-        let folder = sqlx::query_as::<_, Folder>("SELECT * FROM folders WHERE folder_id = $1")
-            .bind(folder_id)
-            .fetch_one(&self.pool)
+        self.load_subtrees(SubtreeRoots::Folder(folder_id))
+            .await?
+            .pop()
+            .ok_or_else(|| AppError::NotFound("Folder not found".to_string()))
+    }
+
+    // The folders below the roots come from one recursive query and their note
+    // counts from one grouped query; the trees are put together in memory
+    async fn load_subtrees(&self, roots: SubtreeRoots) -> Result<Vec<FolderWithChildren>> {
+        let mut query = QueryBuilder::<Postgres>::new(
+            "WITH RECURSIVE subtree AS (SELECT * FROM folders WHERE is_deleted = FALSE AND ",
+        );
+        match roots {
+            SubtreeRoots::TopLevel(user_id) => {
+                query
+                    .push("user_id = ")
+                    .push_bind(user_id)
+                    .push(" AND parent_folder_id IS NULL");
+            }
+            SubtreeRoots::Folder(folder_id) => {
+                query.push("folder_id = ").push_bind(folder_id);
+            }
+        }
+        // UNION rather than UNION ALL, so a folder reached twice is only expanded once
+        query.push(
+            r#"
+            UNION
+            SELECT f.* FROM folders f
+            JOIN subtree s ON f.parent_folder_id = s.folder_id
+            WHERE f.is_deleted = FALSE
+            )
+            SELECT * FROM subtree ORDER BY name, folder_id
+            "#,
+        );
+        let folders = query
+            .build_query_as::<Folder>()
+            .fetch_all(&self.pool)
             .await?;
 
-        let children = sqlx::query_as::<_, Folder>(
-            "SELECT * FROM folders WHERE parent_folder_id = $1 ORDER BY name",
+        let root_ids: Vec<i32> = folders
+            .iter()
+            .filter(|folder| match roots {
+                SubtreeRoots::TopLevel(_) => folder.parent_folder_id.is_none(),
+                SubtreeRoots::Folder(folder_id) => folder.folder_id == folder_id,
+            })
+            .map(|folder| folder.folder_id)
+            .collect();
+        let folder_ids: Vec<i32> = folders.iter().map(|folder| folder.folder_id).collect();
+        let note_counts = self.count_notes(&folder_ids).await?;
+
+        Ok(assemble_trees(&folders, &root_ids, &note_counts))
+    }
+
+    // Notes directly in each folder
+    async fn count_notes(&self, folder_ids: &[i32]) -> Result<HashMap<i32, i64>> {
+        let counts: Vec<(i32, i64)> = sqlx::query_as(
+            r#"
+            SELECT folder_id, COUNT(*) FROM notes
+            WHERE folder_id = ANY($1) AND is_deleted = FALSE
+            GROUP BY folder_id
+            "#,
         )
-        .bind(folder_id)
+        .bind(folder_ids)
         .fetch_all(&self.pool)
         .await?;
 
-        let mut child_trees = Vec::new();
-        for child in children {
-            let child_tree = self.get_folder_with_children(child.folder_id).await?;
-            child_trees.push(child_tree);
+        Ok(counts.into_iter().collect())
+    }
+}
+
+// Callers that look a folder up by name and create it when missing wait for
+// each other, so two of them never both create it. The unique constraint on
+// folder names does not cover top-level folders, whose parent is NULL.
+async fn lock_folder_paths(tx: &mut Transaction<'_, Postgres>, user_id: i32) -> Result<()> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('folder_path'), $1)")
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+// Moves of the same user's folders run one at a time. Otherwise two moves
+// (A under B, B under A) could each check the other's old ancestors and
+// together commit a loop. Taken before the moved folder's row is locked, so
+// waiting movers hold no row locks the current one needs.
+async fn lock_folder_moves(tx: &mut Transaction<'_, Postgres>, user_id: i32) -> Result<()> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('folder_move'), $1)")
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+// Check and apply a new parent for `folder`, which is called `name` there:
+// the parent must be a live folder of the same user, outside the folder's own
+// subtree, deep enough to leave room for the subtree, and free of a sibling
+// with that name. Callers hold `lock_folder_moves`.
+async fn reparent(
+    tx: &mut Transaction<'_, Postgres>,
+    folder: &Folder,
+    parent_folder_id: Option<i32>,
+    name: &str,
+) -> Result<()> {
+    let parent_depth = match parent_folder_id {
+        None => 0,
+        Some(parent_folder_id) => {
+            if parent_folder_id == folder.folder_id {
+                return Err(AppError::InvalidInput(
+                    "A folder cannot be moved into itself".to_string(),
+                ));
+            }
+            ensure_target_folder(tx, parent_folder_id, folder.user_id).await?;
+
+            // Finding the folder among the new parent's ancestors means the
+            // move would close a loop
+            let ancestors = folder_ancestors(tx, parent_folder_id).await?;
+            if ancestors.contains(&folder.folder_id) {
+                return Err(AppError::InvalidInput(
+                    "A folder cannot be moved into one of its own subfolders".to_string(),
+                ));
+            }
+            ancestors.len() as i32
         }
+    };
 
-        let note_count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM notes WHERE folder_id = $1 AND is_deleted = FALSE",
+    ensure_depth_allowed(tx, folder.folder_id, parent_depth).await?;
+    ensure_name_free(tx, folder, parent_folder_id, name).await?;
+
+    // Parent and name change together, so the row never sits under the new
+    // parent with its old name
+    sqlx::query(
+        r#"
+        UPDATE folders SET parent_folder_id = $1, name = $2, updated_at = NOW()
+        WHERE folder_id = $3
+        "#,
+    )
+    .bind(parent_folder_id)
+    .bind(name)
+    .bind(folder.folder_id)
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| folder_name_taken(e, name))?;
+
+    Ok(())
+}
+
+async fn ensure_target_folder(
+    tx: &mut Transaction<'_, Postgres>,
+    folder_id: i32,
+    user_id: i32,
+) -> Result<()> {
+    let exists: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM folders
+            WHERE folder_id = $1 AND user_id = $2 AND is_deleted = FALSE
         )
-        .bind(folder_id)
-        .fetch_one(&self.pool)
-        .await?;
+        "#,
+    )
+    .bind(folder_id)
+    .bind(user_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    if !exists {
+        return Err(AppError::NotFound("Target folder not found".to_string()));
+    }
+    Ok(())
+}
+
+// The folder and its ancestors, nearest first
+async fn folder_ancestors(tx: &mut Transaction<'_, Postgres>, folder_id: i32) -> Result<Vec<i32>> {
+    let ancestors: Vec<i32> = sqlx::query_scalar(
+        r#"
+        WITH RECURSIVE ancestors AS (
+            SELECT folder_id, parent_folder_id, 1 AS depth
+            FROM folders WHERE folder_id = $1
+            UNION ALL
+            SELECT f.folder_id, f.parent_folder_id, a.depth + 1
+            FROM folders f
+            JOIN ancestors a ON f.folder_id = a.parent_folder_id
+            WHERE a.depth <= $2
+        )
+        SELECT folder_id FROM ancestors ORDER BY depth
+        "#,
+    )
+    .bind(folder_id)
+    .bind(MAX_FOLDER_DEPTH)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(ancestors)
+}
+
+// Whether the folder's subtree fits below a parent `parent_depth` levels deep
+async fn ensure_depth_allowed(
+    tx: &mut Transaction<'_, Postgres>,
+    folder_id: i32,
+    parent_depth: i32,
+) -> Result<()> {
+    // Levels the folder and its subfolders take up, counting the folder itself
+    let subtree_height: i32 = sqlx::query_scalar(
+        r#"
+        WITH RECURSIVE subtree AS (
+            SELECT folder_id, 1 AS depth FROM folders WHERE folder_id = $1
+            UNION ALL
+            SELECT f.folder_id, s.depth + 1
+            FROM folders f
+            JOIN subtree s ON f.parent_folder_id = s.folder_id
+            WHERE f.is_deleted = FALSE AND s.depth <= $2
+        )
+        SELECT MAX(depth) FROM subtree
+        "#,
+    )
+    .bind(folder_id)
+    .bind(MAX_FOLDER_DEPTH)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    if parent_depth + subtree_height > MAX_FOLDER_DEPTH {
+        return Err(AppError::ValidationError(format!(
+            "Folders cannot be nested more than {} levels deep",
+            MAX_FOLDER_DEPTH
+        )));
+    }
+    Ok(())
+}
+
+// The live child of `parent_folder_id` (or top-level folder) called `name`,
+// ignoring case but preferring the exact spelling
+async fn find_child(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: i32,
+    parent_folder_id: Option<i32>,
+    name: &str,
+) -> Result<Option<Folder>> {
+    let folder = sqlx::query_as::<_, Folder>(
+        r#"
+        SELECT * FROM folders
+        WHERE user_id = $1 AND parent_folder_id IS NOT DISTINCT FROM $2
+          AND LOWER(name) = LOWER($3) AND is_deleted = FALSE
+        ORDER BY name = $3 DESC, folder_id
+        LIMIT 1
+        "#,
+    )
+    .bind(user_id)
+    .bind(parent_folder_id)
+    .bind(name)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(folder)
+}
+
+fn to_folder_path(folders: Vec<Folder>) -> FolderPath {
+    let names: Vec<&str> = folders.iter().map(|folder| folder.name.as_str()).collect();
+    FolderPath {
+        path: format_folder_path(&names),
+        folders,
+    }
+}
+
+// Live notes in `folder_ids` go to `target`, or out of any folder when None
+async fn move_notes(
+    tx: &mut Transaction<'_, Postgres>,
+    folder_ids: &[i32],
+    target: Option<i32>,
+) -> Result<u64> {
+    let moved = sqlx::query(
+        r#"
+        UPDATE notes SET folder_id = $1, updated_at = NOW()
+        WHERE folder_id = ANY($2) AND is_deleted = FALSE
+        "#,
+    )
+    .bind(target)
+    .bind(folder_ids)
+    .execute(&mut **tx)
+    .await?
+    .rows_affected();
+
+    Ok(moved)
+}
 
-        Ok(FolderWithChildren {
-            folder,
-            children: child_trees,
-            note_count,
+// Folders keep their parent in the trash so a restore puts them back in place.
+// Everything trashed in one transaction shares its `deleted_at`, which is how
+// restoring a folder finds the contents that were trashed along with it.
+async fn trash_folders(tx: &mut Transaction<'_, Postgres>, folder_ids: &[i32]) -> Result<u64> {
+    let trashed = sqlx::query(
+        r#"
+        UPDATE folders SET is_deleted = TRUE, deleted_at = NOW()
+        WHERE folder_id = ANY($1) AND is_deleted = FALSE
+        "#,
+    )
+    .bind(folder_ids)
+    .execute(&mut **tx)
+    .await?
+    .rows_affected();
+
+    Ok(trashed)
+}
+
+// Whether a live folder other than `except_folder_id` already has exactly
+// this name there
+async fn name_in_use(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: i32,
+    parent_folder_id: Option<i32>,
+    name: &str,
+    except_folder_id: Option<i32>,
+) -> Result<bool> {
+    let taken: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM folders
+            WHERE user_id = $1 AND parent_folder_id IS NOT DISTINCT FROM $2
+              AND name = $3 AND folder_id IS DISTINCT FROM $4 AND is_deleted = FALSE
+        )
+        "#,
+    )
+    .bind(user_id)
+    .bind(parent_folder_id)
+    .bind(name)
+    .bind(except_folder_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(taken)
+}
+
+// The unique index treats top-level folders (a NULL parent) as never
+// clashing, so siblings are compared here as well
+async fn ensure_name_free(
+    tx: &mut Transaction<'_, Postgres>,
+    folder: &Folder,
+    parent_folder_id: Option<i32>,
+    name: &str,
+) -> Result<()> {
+    if name_in_use(
+        tx,
+        folder.user_id,
+        parent_folder_id,
+        name,
+        Some(folder.folder_id),
+    )
+    .await?
+    {
+        return Err(name_conflict(name));
+    }
+    Ok(())
+}
+
+fn folder_name_taken(err: sqlx::Error, name: &str) -> AppError {
+    match &err {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => name_conflict(name),
+        _ => err.into(),
+    }
+}
+
+fn name_conflict(name: &str) -> AppError {
+    AppError::Conflict {
+        message: format!("A folder named \"{}\" already exists there", name),
+        details: None,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SubtreeRoots {
+    // The user's top-level folders
+    TopLevel(i32),
+    Folder(i32),
+}
+
+// Build the tree under each root from a flat list of folders, keeping the
+// list's order among siblings
+fn assemble_trees(
+    folders: &[Folder],
+    roots: &[i32],
+    note_counts: &HashMap<i32, i64>,
+) -> Vec<FolderWithChildren> {
+    let by_id: HashMap<i32, &Folder> = folders
+        .iter()
+        .map(|folder| (folder.folder_id, folder))
+        .collect();
+    let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+    for folder in folders {
+        if let Some(parent_id) = folder.parent_folder_id {
+            children
+                .entry(parent_id)
+                .or_default()
+                .push(folder.folder_id);
+        }
+    }
+
+    roots
+        .iter()
+        .filter_map(|folder_id| {
+            let mut path = HashSet::new();
+            build_tree(*folder_id, &by_id, &children, note_counts, &mut path)
         })
+        .collect()
+}
+
+fn build_tree(
+    folder_id: i32,
+    by_id: &HashMap<i32, &Folder>,
+    children: &HashMap<i32, Vec<i32>>,
+    note_counts: &HashMap<i32, i64>,
+    path: &mut HashSet<i32>,
+) -> Option<FolderWithChildren> {
+    // A folder that is its own ancestor is cut off rather than followed forever
+    if !path.insert(folder_id) {
+        return None;
     }
+    let folder = (*by_id.get(&folder_id)?).clone();
+
+    let child_trees: Vec<FolderWithChildren> = children
+        .get(&folder_id)
+        .into_iter()
+        .flatten()
+        .filter_map(|child_id| build_tree(*child_id, by_id, children, note_counts, path))
+        .collect();
+    path.remove(&folder_id);
+
+    let note_count = note_counts.get(&folder_id).copied().unwrap_or(0);
+    let recursive_note_count = note_count
+        + child_trees
+            .iter()
+            .map(|child| child.recursive_note_count)
+            .sum::<i64>();
+
+    Some(FolderWithChildren {
+        folder,
+        children: child_trees,
+        note_count,
+        recursive_note_count,
+    })
 }
 
 #[derive(Debug)]
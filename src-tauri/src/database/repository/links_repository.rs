@@ -0,0 +1,273 @@
+use super::super::models::link::{
+    Backlink, LinkRewriteNote, LinkRewritePreview, OutgoingLink, OutgoingLinks, UnresolvedLink,
+};
+use super::super::models::note::Note;
+use super::properties_repository::PropertyRepository;
+use super::revisions_repository::RevisionRepository;
+use super::tasks_repository::TaskRepository;
+use crate::utils::error::{AppError, Result};
+use crate::utils::wiki_links::{is_linkable_title, parse_wiki_links, rewrite_wiki_links};
+use sqlx::{Pool, Postgres, Transaction};
+
+#[derive(Debug, Clone)]
+pub struct LinkRepository {
+    pool: Pool<Postgres>,
+}
+
+impl LinkRepository {
+    #[allow(dead_code)]
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Replace a note's outgoing links with the ones in its current content.
+    // Runs inside the caller's transaction, next to the note write itself.
+    pub async fn sync_note_links(tx: &mut Transaction<'_, Postgres>, note: &Note) -> Result<()> {
+        sqlx::query("DELETE FROM note_links WHERE source_note_id = $1")
+            .bind(note.note_id)
+            .execute(&mut **tx)
+            .await?;
+
+        let links = parse_wiki_links(&note.content);
+        if links.is_empty() {
+            return Ok(());
+        }
+
+        let mut titles = Vec::with_capacity(links.len());
+        let mut aliases = Vec::with_capacity(links.len());
+        let mut contexts = Vec::with_capacity(links.len());
+        for link in links {
+            titles.push(link.target);
+            aliases.push(link.alias);
+            contexts.push(link.context);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO note_links (source_note_id, target_note_id, target_title, alias, context)
+            SELECT $1,
+                   (SELECT t.note_id FROM notes t
+                    WHERE t.user_id = $2 AND t.is_deleted = FALSE
+                      AND LOWER(t.title) = LOWER(l.target_title)
+                    ORDER BY t.note_id LIMIT 1),
+                   l.target_title, l.alias, l.context
+            FROM UNNEST($3::TEXT[], $4::TEXT[], $5::TEXT[]) AS l(target_title, alias, context)
+            "#,
+        )
+        .bind(note.note_id)
+        .bind(note.user_id)
+        .bind(&titles)
+        .bind(&aliases)
+        .bind(&contexts)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    // Point every link to one of `titles` at the live note that carries the
+    // title now, or mark it dangling. Called whenever a note gains, changes
+    // or loses a title: on create, rename, trash and restore. When several
+    // notes share a title the oldest one wins.
+    pub async fn resolve_titles(
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        titles: &[String],
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE note_links l
+            SET target_note_id = (
+                SELECT t.note_id FROM notes t
+                WHERE t.user_id = $1 AND t.is_deleted = FALSE
+                  AND LOWER(t.title) = LOWER(l.target_title)
+                ORDER BY t.note_id LIMIT 1
+            )
+            FROM notes s
+            WHERE s.note_id = l.source_note_id AND s.user_id = $1
+              AND LOWER(l.target_title) IN (SELECT LOWER(title) FROM UNNEST($2::TEXT[]) AS title)
+            "#,
+        )
+        .bind(user_id)
+        .bind(titles)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    // Rewrite `[[old_title]]` links to `new_title` in every other note that
+    // links to `note_id`. Each changed note gets a revision and fresh links,
+    // all in the caller's transaction. Must run before `resolve_titles`
+    // detaches the old links from the renamed note.
+    pub async fn rewrite_links_to(
+        tx: &mut Transaction<'_, Postgres>,
+        note_id: i32,
+        user_id: i32,
+        old_title: &str,
+        new_title: &str,
+    ) -> Result<Vec<LinkRewriteNote>> {
+        let sources = sqlx::query_as::<_, Note>(
+            r#"
+            SELECT n.* FROM notes n
+            WHERE n.user_id = $2 AND n.is_deleted = FALSE AND n.note_id <> $1
+              AND n.note_id IN (SELECT source_note_id FROM note_links WHERE target_note_id = $1)
+            ORDER BY n.note_id
+            FOR UPDATE
+            "#,
+        )
+        .bind(note_id)
+        .bind(user_id)
+        .fetch_all(&mut **tx)
+        .await?;
+
+        let mut rewritten = Vec::new();
+        for source in sources {
+            let (content, occurrences) = rewrite_wiki_links(&source.content, old_title, new_title);
+            if occurrences == 0 {
+                continue;
+            }
+
+            RevisionRepository::record_revision(tx, &source).await?;
+
+            let updated = sqlx::query_as::<_, Note>(
+                "UPDATE notes SET content = $1, updated_at = NOW() WHERE note_id = $2 RETURNING *",
+            )
+            .bind(&content)
+            .bind(source.note_id)
+            .fetch_one(&mut **tx)
+            .await?;
+
+            Self::sync_note_links(tx, &updated).await?;
+            TaskRepository::sync_note_tasks(tx, &updated).await?;
+            PropertyRepository::sync_note_properties(tx, &updated).await?;
+
+            rewritten.push(LinkRewriteNote {
+                note_id: updated.note_id,
+                title: updated.title,
+                occurrences,
+            });
+        }
+
+        Ok(rewritten)
+    }
+
+    // Dry run of `rewrite_links_to` for renaming `note_id` to `new_title`
+    pub async fn preview_link_rewrite(
+        &self,
+        note_id: i32,
+        user_id: i32,
+        new_title: &str,
+    ) -> Result<LinkRewritePreview> {
+        if !is_linkable_title(new_title) {
+            return Err(AppError::ValidationError(
+                "Links cannot be rewritten to a title containing '|', '[[', ']]' or a line break"
+                    .to_string(),
+            ));
+        }
+
+        let old_title: String = sqlx::query_scalar(
+            "SELECT title FROM notes WHERE note_id = $1 AND user_id = $2 AND is_deleted = FALSE",
+        )
+        .bind(note_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Note not found".to_string()))?;
+
+        let sources = sqlx::query_as::<_, Note>(
+            r#"
+            SELECT n.* FROM notes n
+            WHERE n.user_id = $2 AND n.is_deleted = FALSE AND n.note_id <> $1
+              AND n.note_id IN (SELECT source_note_id FROM note_links WHERE target_note_id = $1)
+            ORDER BY n.note_id
+            "#,
+        )
+        .bind(note_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let notes: Vec<LinkRewriteNote> = sources
+            .into_iter()
+            .filter_map(|source| {
+                let (_, occurrences) = rewrite_wiki_links(&source.content, &old_title, new_title);
+                (occurrences > 0).then_some(LinkRewriteNote {
+                    note_id: source.note_id,
+                    title: source.title,
+                    occurrences,
+                })
+            })
+            .collect();
+
+        Ok(LinkRewritePreview {
+            note_id,
+            old_title,
+            new_title: new_title.to_string(),
+            total_occurrences: notes.iter().map(|note| note.occurrences).sum(),
+            notes,
+        })
+    }
+
+    pub async fn get_backlinks(&self, note_id: i32, user_id: i32) -> Result<Vec<Backlink>> {
+        let backlinks = sqlx::query_as::<_, Backlink>(
+            r#"
+            SELECT l.link_id, l.source_note_id, s.title AS source_title,
+                   l.alias, l.context, s.updated_at AS source_updated_at
+            FROM note_links l
+            INNER JOIN notes s ON s.note_id = l.source_note_id
+            WHERE l.target_note_id = $1 AND s.user_id = $2 AND s.is_deleted = FALSE
+            ORDER BY s.updated_at DESC, l.link_id
+            "#,
+        )
+        .bind(note_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(backlinks)
+    }
+
+    pub async fn get_outgoing_links(&self, note_id: i32, user_id: i32) -> Result<OutgoingLinks> {
+        let links = sqlx::query_as::<_, OutgoingLink>(
+            r#"
+            SELECT l.link_id, l.target_note_id, l.target_title, l.alias, l.context
+            FROM note_links l
+            INNER JOIN notes s ON s.note_id = l.source_note_id
+            WHERE l.source_note_id = $1 AND s.user_id = $2
+            ORDER BY l.link_id
+            "#,
+        )
+        .bind(note_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let (resolved, dangling) = links
+            .into_iter()
+            .partition(|link| link.target_note_id.is_some());
+
+        Ok(OutgoingLinks { resolved, dangling })
+    }
+
+    // Dangling link targets across the user's notes, most linked first
+    pub async fn get_unresolved_links(&self, user_id: i32) -> Result<Vec<UnresolvedLink>> {
+        let links = sqlx::query_as::<_, UnresolvedLink>(
+            r#"
+            SELECT MIN(l.target_title) AS target_title,
+                   COUNT(*) AS occurrences,
+                   json_agg(DISTINCT jsonb_build_object('note_id', s.note_id, 'title', s.title)) AS sources
+            FROM note_links l
+            INNER JOIN notes s ON s.note_id = l.source_note_id
+            WHERE s.user_id = $1 AND s.is_deleted = FALSE AND l.target_note_id IS NULL
+            GROUP BY LOWER(l.target_title)
+            ORDER BY COUNT(*) DESC, LOWER(l.target_title)
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(links)
+    }
+}
@@ -1,7 +1,16 @@
 pub mod attachments_repository;
 pub mod folders_repository;
+pub mod links_repository;
 pub mod notes_repository;
+pub mod properties_repository;
+pub mod reminders_repository;
+pub mod revisions_repository;
+pub mod saved_searches_repository;
+pub mod similarity_repository;
 pub mod tags_repository;
+pub mod tasks_repository;
+pub mod templates_repository;
+pub mod trash_repository;
 pub mod users_repository;
 
 #[allow(unused_imports)]
@@ -9,8 +18,26 @@ pub use attachments_repository::*;
 #[allow(unused_imports)]
 pub use folders_repository::*;
 #[allow(unused_imports)]
+pub use links_repository::*;
+#[allow(unused_imports)]
 pub use notes_repository::*;
 #[allow(unused_imports)]
+pub use properties_repository::*;
+#[allow(unused_imports)]
+pub use reminders_repository::*;
+#[allow(unused_imports)]
+pub use revisions_repository::*;
+#[allow(unused_imports)]
+pub use saved_searches_repository::*;
+#[allow(unused_imports)]
+pub use similarity_repository::*;
+#[allow(unused_imports)]
 pub use tags_repository::*;
 #[allow(unused_imports)]
+pub use tasks_repository::*;
+#[allow(unused_imports)]
+pub use templates_repository::*;
+#[allow(unused_imports)]
+pub use trash_repository::*;
+#[allow(unused_imports)]
 pub use users_repository::*;
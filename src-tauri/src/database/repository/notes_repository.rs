@@ -1,7 +1,34 @@
-use super::super::models::note::{FolderInfo, Note, NoteWithRelations, TagInfo};
-use crate::utils::error::Result;
-use chrono::Utc;
-use sqlx::{Pool, Postgres};
+use super::super::models::attachment::Attachment;
+use super::super::models::bulk::{BulkNoteOperations, BulkNoteResult, BulkUpdateSummary};
+use super::super::models::journal::{AdjacentDailyNotes, DailyNoteSummary};
+use super::super::models::note::{FolderInfo, Note, NoteConflict, NoteWithRelations, TagInfo};
+use super::super::models::note_query::{
+    CursorValue, NoteCursor, NotePage, NoteQuery, NoteSortField, SortDirection,
+    DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE,
+};
+use super::super::models::property::PropertyNoteQuery;
+use super::super::models::search::{
+    NoteHeadline, RankedNote, SearchMode, SearchResult, Snippet, CONTENT_HEADLINE_OPTIONS,
+    TITLE_HEADLINE_OPTIONS,
+};
+use super::links_repository::LinkRepository;
+use super::properties_repository::{push_property_expr, PropertyRepository};
+use super::revisions_repository::RevisionRepository;
+use super::tasks_repository::TaskRepository;
+use super::trash_repository::remove_files;
+use crate::config::get_attachments_dir;
+use crate::utils::error::{AppError, Result};
+use crate::utils::helpers::copy_name;
+use crate::utils::merge::three_way_merge;
+use crate::utils::property_query::PropertyExpr;
+use crate::utils::search_query::{NoteFlag, SearchExpr, SearchTerm};
+use crate::utils::validation::validate_tag_name;
+use crate::utils::wiki_links::is_linkable_title;
+use chrono::{Datelike, NaiveDate, NaiveTime, Utc};
+use sqlx::types::Json;
+use sqlx::{Acquire, FromRow, Pool, Postgres, QueryBuilder, Transaction};
+use std::collections::HashMap;
+use std::path::Path;
 
 #[derive(Debug, Clone)]
 pub struct NoteRepository {
@@ -20,75 +47,314 @@ impl NoteRepository {
         tags: &[String],
     ) -> Result<NoteWithRelations> {
         let mut tx = self.pool.begin().await?;
+        let note = self.insert_note(&mut tx, &dto, tags, None).await?;
+        tx.commit().await?;
+
+        // Fetch with relations
+        self.get_note_with_relations(note.note_id).await
+    }
+
+    pub async fn find_daily_note(
+        &self,
+        user_id: i32,
+        date: NaiveDate,
+    ) -> Result<Option<NoteWithRelations>> {
+        let note = sqlx::query_as::<_, Note>(
+            "SELECT * FROM notes WHERE user_id = $1 AND journal_date = $2 AND is_deleted = FALSE",
+        )
+        .bind(user_id)
+        .bind(date)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match note {
+            Some(note) => Ok(self.load_relations(vec![note]).await?.pop()),
+            None => Ok(None),
+        }
+    }
+
+    // Create the journal note for `date`, or return the existing one. Opens
+    // for the same user and day take a transaction-scoped advisory lock, so a
+    // second caller waits for the first and then finds its note instead of
+    // inserting a duplicate.
+    pub async fn create_daily_note(
+        &self,
+        date: NaiveDate,
+        dto: CreateNoteDto,
+        tags: &[String],
+    ) -> Result<NoteWithRelations> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("SELECT pg_advisory_xact_lock($1, $2)")
+            .bind(dto.user_id)
+            .bind(date.num_days_from_ce())
+            .execute(&mut *tx)
+            .await?;
+
+        let existing: Option<i32> = sqlx::query_scalar(
+            "SELECT note_id FROM notes WHERE user_id = $1 AND journal_date = $2 AND is_deleted = FALSE",
+        )
+        .bind(dto.user_id)
+        .bind(date)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let note_id = match existing {
+            Some(note_id) => note_id,
+            None => {
+                self.insert_note(&mut tx, &dto, tags, Some(date))
+                    .await?
+                    .note_id
+            }
+        };
+
+        tx.commit().await?;
+        self.get_note_with_relations(note_id).await
+    }
+
+    pub async fn list_daily_notes(
+        &self,
+        user_id: i32,
+        from: NaiveDate,
+        until: NaiveDate,
+    ) -> Result<Vec<DailyNoteSummary>> {
+        let notes = sqlx::query_as::<_, DailyNoteSummary>(
+            r#"
+            SELECT note_id, journal_date, title FROM notes
+            WHERE user_id = $1 AND is_deleted = FALSE
+              AND journal_date >= $2 AND journal_date < $3
+            ORDER BY journal_date
+            "#,
+        )
+        .bind(user_id)
+        .bind(from)
+        .bind(until)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(notes)
+    }
+
+    // The closest journal entries before and after `date`, skipping days without a note
+    pub async fn get_adjacent_daily_notes(
+        &self,
+        user_id: i32,
+        date: NaiveDate,
+    ) -> Result<AdjacentDailyNotes> {
+        let previous = sqlx::query_as::<_, DailyNoteSummary>(
+            r#"
+            SELECT note_id, journal_date, title FROM notes
+            WHERE user_id = $1 AND is_deleted = FALSE AND journal_date < $2
+            ORDER BY journal_date DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(user_id)
+        .bind(date)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let next = sqlx::query_as::<_, DailyNoteSummary>(
+            r#"
+            SELECT note_id, journal_date, title FROM notes
+            WHERE user_id = $1 AND is_deleted = FALSE AND journal_date > $2
+            ORDER BY journal_date
+            LIMIT 1
+            "#,
+        )
+        .bind(user_id)
+        .bind(date)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(AdjacentDailyNotes { previous, next })
+    }
+
+    async fn insert_note(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        dto: &CreateNoteDto,
+        tags: &[String],
+        journal_date: Option<NaiveDate>,
+    ) -> Result<Note> {
+        let (folder_id, tags) = Self::apply_template_defaults(tx, dto, tags).await?;
 
         // Create note
         let note = sqlx::query_as::<_, Note>(
             r#"
-            INSERT INTO notes (user_id, folder_id, title, content, is_pinned)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO notes (user_id, folder_id, title, content, is_pinned, journal_date)
+            VALUES ($1, $2, $3, $4, $5, $6)
             RETURNING *
             "#,
         )
         .bind(dto.user_id)
-        .bind(dto.folder_id)
+        .bind(folder_id)
         .bind(&dto.title)
         .bind(&dto.content)
         .bind(dto.is_pinned)
-        .fetch_one(&mut *tx)
+        .bind(journal_date)
+        .fetch_one(&mut **tx)
         .await?;
 
         // Add tags
-        for tag_name in tags {
-            if let Some(tag) = self
-                .get_or_create_tag(dto.user_id, tag_name, &mut tx)
-                .await?
-            {
+        for tag_name in &tags {
+            if let Some(tag) = self.get_or_create_tag(dto.user_id, tag_name, tx).await? {
                 sqlx::query(
                     "INSERT INTO note_tags (note_id, tag_id) VALUES ($1, $2) ON CONFLICT DO NOTHING"
                 )
                 .bind(note.note_id)
                 .bind(tag.tag_id)
-                .execute(&mut *tx)
+                .execute(&mut **tx)
                 .await?;
             }
         }
 
-        tx.commit().await?;
+        LinkRepository::sync_note_links(tx, &note).await?;
+        TaskRepository::sync_note_tasks(tx, &note).await?;
+        PropertyRepository::sync_note_properties(tx, &note).await?;
+        LinkRepository::resolve_titles(tx, note.user_id, std::slice::from_ref(&note.title)).await?;
 
-        // Fetch with relations
-        self.get_note_with_relations(note.note_id).await
+        Ok(note)
     }
 
-    pub async fn get_note_by_id(&self, note_id: i32) -> Result<Option<NoteWithRelations>> {
-        let note = sqlx::query_as::<_, Note>(
-            "SELECT * FROM notes WHERE note_id = $1 AND is_deleted = FALSE",
+    // A note created from a template goes to the template's default folder
+    // unless another was chosen, and gets the template's default tags ahead
+    // of its own. A default folder that no longer exists is ignored.
+    async fn apply_template_defaults(
+        tx: &mut Transaction<'_, Postgres>,
+        dto: &CreateNoteDto,
+        tags: &[String],
+    ) -> Result<(Option<i32>, Vec<String>)> {
+        let Some(template_id) = dto.template_id else {
+            return Ok((dto.folder_id, tags.to_vec()));
+        };
+
+        let (default_folder_id, mut all_tags): (Option<i32>, Vec<String>) = sqlx::query_as(
+            r#"
+            SELECT f.folder_id, t.default_tags
+            FROM note_templates t
+            LEFT JOIN folders f ON f.folder_id = t.default_folder_id
+                AND f.user_id = t.user_id AND f.is_deleted = FALSE
+            WHERE t.template_id = $1 AND t.user_id = $2
+            "#,
         )
-        .bind(note_id)
-        .fetch_optional(&self.pool)
+        .bind(template_id)
+        .bind(dto.user_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Template not found".to_string()))?;
+
+        for tag in tags {
+            if !all_tags.contains(tag) {
+                all_tags.push(tag.clone());
+            }
+        }
+
+        Ok((dto.folder_id.or(default_folder_id), all_tags))
+    }
+
+    // Insert a copy of `source` as a new note of the same user. Copied
+    // attachment files are recorded in `copied_files`, so the caller can
+    // remove them again if the transaction does not commit.
+    pub(crate) async fn copy_note(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        source: &Note,
+        folder_id: Option<i32>,
+        title: String,
+        copy_attachments: bool,
+        copied_files: &mut Vec<String>,
+    ) -> Result<Note> {
+        let tags: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT t.name FROM tags t
+            INNER JOIN note_tags nt ON nt.tag_id = t.tag_id
+            WHERE nt.note_id = $1 AND t.is_deleted = FALSE
+            ORDER BY t.name
+            "#,
+        )
+        .bind(source.note_id)
+        .fetch_all(&mut **tx)
         .await?;
 
-        if let Some(note) = note {
-            self.get_note_with_relations(note.note_id).await.map(Some)
-        } else {
-            Ok(None)
+        let dto = CreateNoteDto {
+            user_id: source.user_id,
+            title,
+            content: source.content.clone(),
+            folder_id,
+            is_pinned: source.is_pinned,
+            template_id: None,
+        };
+        let mut note = self.insert_note(tx, &dto, &tags, None).await?;
+
+        if source.is_archived {
+            note = sqlx::query_as::<_, Note>(
+                "UPDATE notes SET is_archived = TRUE WHERE note_id = $1 RETURNING *",
+            )
+            .bind(note.note_id)
+            .fetch_one(&mut **tx)
+            .await?;
+        }
+
+        if copy_attachments {
+            let attachments = sqlx::query_as::<_, Attachment>(
+                "SELECT * FROM attachments WHERE note_id = $1 ORDER BY attachment_id",
+            )
+            .bind(source.note_id)
+            .fetch_all(&mut **tx)
+            .await?;
+
+            // Each copy gets its own files, so purging one note never removes
+            // the files of another. The source attachment's id keeps two
+            // attachments with the same file name apart.
+            let dir = Path::new(&get_attachments_dir()).join(note.note_id.to_string());
+            for attachment in attachments {
+                tokio::fs::create_dir_all(&dir).await?;
+                let file_path = dir
+                    .join(format!(
+                        "{}-{}",
+                        attachment.attachment_id, attachment.file_name
+                    ))
+                    .to_string_lossy()
+                    .to_string();
+                tokio::fs::copy(&attachment.file_path, &file_path).await?;
+                copied_files.push(file_path.clone());
+
+                sqlx::query(
+                    r#"
+                    INSERT INTO attachments (note_id, file_name, file_path, file_size, mime_type)
+                    VALUES ($1, $2, $3, $4, $5)
+                    "#,
+                )
+                .bind(note.note_id)
+                .bind(&attachment.file_name)
+                .bind(&file_path)
+                .bind(attachment.file_size)
+                .bind(&attachment.mime_type)
+                .execute(&mut **tx)
+                .await?;
+            }
         }
+
+        Ok(note)
     }
 
-    pub async fn get_user_notes(&self, user_id: i32) -> Result<Vec<NoteWithRelations>> {
-        let notes = sqlx::query_as::<_, Note>(
-            "SELECT * FROM notes WHERE user_id = $1 AND is_deleted = FALSE ORDER BY created_at DESC"
+    pub async fn get_note_by_id(&self, note_id: i32) -> Result<Option<NoteWithRelations>> {
+        let note = sqlx::query_as::<_, Note>(
+            "SELECT * FROM notes WHERE note_id = $1 AND is_deleted = FALSE",
         )
-        .bind(user_id)
-        .fetch_all(&self.pool)
+        .bind(note_id)
+        .fetch_optional(&self.pool)
         .await?;
 
-        let mut result = Vec::new();
-        for note in notes {
-            let with_relations = self.get_note_with_relations(note.note_id).await?;
-            result.push(with_relations);
+        match note {
+            Some(note) => Ok(self.load_relations(vec![note]).await?.pop()),
+            None => Ok(None),
         }
+    }
 
-        Ok(result)
+    pub async fn get_user_notes(&self, user_id: i32, query: &NoteQuery) -> Result<NotePage> {
+        self.list_notes(user_id, query).await
     }
 
     pub async fn update_note(
@@ -98,60 +364,102 @@ impl NoteRepository {
     ) -> Result<NoteWithRelations> {
         let mut tx = self.pool.begin().await?;
 
+        // Lock the row so concurrent updates serialize around the revision snapshot
+        let current = sqlx::query_as::<_, Note>(
+            "SELECT * FROM notes WHERE note_id = $1 AND is_deleted = FALSE FOR UPDATE",
+        )
+        .bind(dto.note_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Note not found".to_string()))?;
+
+        if let Some(expected_version) = dto.expected_version {
+            if expected_version != current.version {
+                tx.rollback().await?;
+                let conflict = self.version_conflict(&dto, expected_version).await?;
+                return Err(conflict);
+            }
+        }
+
+        let title_changed = dto.title.as_ref().is_some_and(|t| *t != current.title);
+        let content_changed = dto.content.as_ref().is_some_and(|c| *c != current.content);
+
+        if title_changed && dto.rewrite_links {
+            let title = dto.title.as_deref().unwrap_or_default();
+            if !is_linkable_title(title) {
+                return Err(AppError::ValidationError(
+                    "Links cannot be rewritten to a title containing '|', '[[', ']]' or a line break"
+                        .to_string(),
+                ));
+            }
+        }
+
+        if title_changed || content_changed {
+            RevisionRepository::record_revision(&mut tx, &current).await?;
+        }
+
         // Build update query dynamically
-        let mut query = "UPDATE notes SET updated_at = $1".to_string();
-        let mut params: Vec<String> = vec![Utc::now().to_string()];
-        let mut param_count = 2;
+        let mut query = QueryBuilder::<Postgres>::new("UPDATE notes SET updated_at = ");
+        query.push_bind(Utc::now());
 
         if let Some(title) = &dto.title {
-            query.push_str(&format!(", title = ${}", param_count));
-            params.push(title.clone());
-            param_count += 1;
+            query.push(", title = ").push_bind(title);
         }
 
         if let Some(content) = &dto.content {
-            query.push_str(&format!(", content = ${}", param_count));
-            params.push(content.clone());
-            param_count += 1;
+            query.push(", content = ").push_bind(content);
         }
 
         if let Some(folder_id) = dto.folder_id {
-            query.push_str(&format!(", folder_id = ${}", param_count));
-            params.push(
-                folder_id
-                    .map(|id| id.to_string())
-                    .unwrap_or("NULL".to_string()),
-            );
-            param_count += 1;
+            query.push(", folder_id = ").push_bind(folder_id);
         }
 
         if let Some(is_pinned) = dto.is_pinned {
-            query.push_str(&format!(", is_pinned = ${}", param_count));
-            params.push(is_pinned.to_string());
-            param_count += 1;
+            query.push(", is_pinned = ").push_bind(is_pinned);
         }
 
         if let Some(is_archived) = dto.is_archived {
-            query.push_str(&format!(", is_archived = ${}", param_count));
-            params.push(is_archived.to_string());
-            param_count += 1;
+            query.push(", is_archived = ").push_bind(is_archived);
+        }
+
+        if let Some(sort_order) = dto.sort_order {
+            query.push(", sort_order = ").push_bind(sort_order);
         }
 
-        query.push_str(&format!(
-            " WHERE note_id = ${} AND is_deleted = FALSE RETURNING *",
-            param_count
-        ));
-        params.push(dto.note_id.to_string());
+        query
+            .push(" WHERE note_id = ")
+            .push_bind(dto.note_id)
+            .push(" RETURNING *");
 
         // Execute update
-        let note = sqlx::query_as::<_, Note>(&query);
-        let mut query_builder = note;
+        let note = query.build_query_as::<Note>().fetch_one(&mut *tx).await?;
 
-        for param in &params {
-            query_builder = query_builder.bind(param);
+        if content_changed {
+            LinkRepository::sync_note_links(&mut tx, &note).await?;
+            TaskRepository::sync_note_tasks(&mut tx, &note).await?;
+            PropertyRepository::sync_note_properties(&mut tx, &note).await?;
         }
 
-        let note = query_builder.fetch_one(&mut *tx).await?;
+        // Links to the old title dangle (or find another note), links to the new one resolve here
+        if title_changed {
+            if dto.rewrite_links {
+                LinkRepository::rewrite_links_to(
+                    &mut tx,
+                    note.note_id,
+                    note.user_id,
+                    &current.title,
+                    &note.title,
+                )
+                .await?;
+            }
+
+            LinkRepository::resolve_titles(
+                &mut tx,
+                note.user_id,
+                &[current.title.clone(), note.title.clone()],
+            )
+            .await?;
+        }
 
         // Update tags if provided
         if let Some(tag_names) = tags {
@@ -181,129 +489,681 @@ impl NoteRepository {
         self.get_note_with_relations(note.note_id).await
     }
 
-    pub async fn soft_delete_note(&self, note_id: i32, user_id: i32) -> Result<()> {
-        sqlx::query("UPDATE notes SET is_deleted = TRUE WHERE note_id = $1 AND user_id = $2")
-            .bind(note_id)
-            .bind(user_id)
-            .execute(&self.pool)
-            .await?;
-
-        Ok(())
-    }
+    // Describe a stale update: the stored note plus, when the version the
+    // caller edited is still in the revision history, their content merged
+    // with the stored content
+    async fn version_conflict(
+        &self,
+        dto: &UpdateNoteDto,
+        expected_version: i32,
+    ) -> Result<AppError> {
+        let server = self.get_note_with_relations(dto.note_id).await?;
 
-    pub async fn search_notes(&self, user_id: i32, query: &str) -> Result<Vec<NoteWithRelations>> {
-        let notes = sqlx::query_as::<_, Note>(
+        let base: Option<String> = sqlx::query_scalar(
             r#"
-            SELECT n.* FROM notes n
-            WHERE n.user_id = $1 
-            AND n.is_deleted = FALSE
-            AND (n.title ILIKE $2 OR n.content ILIKE $2)
-            ORDER BY n.updated_at DESC
+            SELECT content FROM note_revisions
+            WHERE note_id = $1 AND version = $2
+            ORDER BY revision_number DESC
+            LIMIT 1
             "#,
         )
+        .bind(dto.note_id)
+        .bind(expected_version)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let merge = match (&dto.content, base) {
+            (Some(content), Some(base)) => {
+                Some(three_way_merge(&base, content, &server.note.content))
+            }
+            _ => None,
+        };
+
+        Ok(AppError::Conflict {
+            message: format!(
+                "The note was changed elsewhere (now at version {}, edit based on version {})",
+                server.note.version, expected_version
+            ),
+            details: Some(Box::new(NoteConflict {
+                server,
+                expected_version,
+                merge,
+            })),
+        })
+    }
+
+    pub async fn soft_delete_note(&self, note_id: i32, user_id: i32) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let title: Option<String> = sqlx::query_scalar(
+            "UPDATE notes SET is_deleted = TRUE, deleted_at = NOW() WHERE note_id = $1 AND user_id = $2 AND is_deleted = FALSE RETURNING title"
+        )
+        .bind(note_id)
         .bind(user_id)
-        .bind(format!("%{}%", query))
-        .fetch_all(&self.pool)
+        .fetch_optional(&mut *tx)
         .await?;
 
-        let mut result = Vec::new();
-        for note in notes {
-            let with_relations = self.get_note_with_relations(note.note_id).await?;
-            result.push(with_relations);
+        // Links to a trashed note dangle until it is restored
+        if let Some(title) = title {
+            LinkRepository::resolve_titles(&mut tx, user_id, &[title]).await?;
         }
 
-        Ok(result)
+        tx.commit().await?;
+        Ok(())
     }
 
-    pub async fn get_notes_by_folder(
+    // Copy a note with its tags, and optionally its attachment files. A
+    // `target_folder_id` of None keeps the original's folder; Some(None)
+    // puts the copy at the top level.
+    pub async fn duplicate_note(
         &self,
+        note_id: i32,
         user_id: i32,
-        folder_id: i32,
-    ) -> Result<Vec<NoteWithRelations>> {
-        let notes = sqlx::query_as::<_, Note>(
-            "SELECT * FROM notes WHERE user_id = $1 AND folder_id = $2 AND is_deleted = FALSE ORDER BY created_at DESC"
+        target_folder_id: Option<Option<i32>>,
+        copy_attachments: bool,
+    ) -> Result<NoteWithRelations> {
+        let mut tx = self.pool.begin().await?;
+
+        let source = sqlx::query_as::<_, Note>(
+            "SELECT * FROM notes WHERE note_id = $1 AND user_id = $2 AND is_deleted = FALSE",
         )
+        .bind(note_id)
         .bind(user_id)
-        .bind(folder_id)
-        .fetch_all(&self.pool)
-        .await?;
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Note not found".to_string()))?;
 
-        let mut result = Vec::new();
-        for note in notes {
-            let with_relations = self.get_note_with_relations(note.note_id).await?;
-            result.push(with_relations);
+        let folder_id = target_folder_id.unwrap_or(source.folder_id);
+        if let Some(folder_id) = folder_id {
+            let exists: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM folders WHERE folder_id = $1 AND user_id = $2 AND is_deleted = FALSE)",
+            )
+            .bind(folder_id)
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if !exists {
+                return Err(AppError::NotFound("Folder not found".to_string()));
+            }
         }
 
-        Ok(result)
+        // A copy next to its original is told apart by a " (copy)" suffix
+        let mut title = source.title.clone();
+        let mut attempt = 0;
+        while title_taken(&mut tx, user_id, folder_id, &title).await? {
+            attempt += 1;
+            title = copy_name(&source.title, attempt, MAX_TITLE_LENGTH);
+        }
+
+        let mut copied_files = Vec::new();
+        let copied = async {
+            let note = self
+                .copy_note(
+                    &mut tx,
+                    &source,
+                    folder_id,
+                    title,
+                    copy_attachments,
+                    &mut copied_files,
+                )
+                .await?;
+            tx.commit().await?;
+            Ok::<_, AppError>(note)
+        }
+        .await;
+
+        match copied {
+            Ok(note) => self.get_note_with_relations(note.note_id).await,
+            Err(e) => {
+                remove_files(&copied_files).await;
+                Err(e)
+            }
+        }
     }
 
-    pub async fn get_pinned_notes(&self, user_id: i32) -> Result<Vec<NoteWithRelations>> {
-        let notes = sqlx::query_as::<_, Note>(
-            "SELECT * FROM notes WHERE user_id = $1 AND is_pinned = TRUE AND is_deleted = FALSE ORDER BY updated_at DESC"
+    // Apply one set of changes to many notes in a single transaction. Every
+    // note gets its own savepoint, so a note that is missing, belongs to
+    // another user or fails to update is reported in its result and left
+    // untouched while the rest are still applied.
+    pub async fn bulk_update_notes(
+        &self,
+        user_id: i32,
+        note_ids: &[i32],
+        operations: &BulkNoteOperations,
+    ) -> Result<BulkUpdateSummary> {
+        if operations.is_empty() {
+            return Err(AppError::ValidationError(
+                "No changes were requested".to_string(),
+            ));
+        }
+        if note_ids.is_empty() {
+            return Err(AppError::ValidationError(
+                "No notes were selected".to_string(),
+            ));
+        }
+        if note_ids.len() > MAX_BULK_NOTES {
+            return Err(AppError::ValidationError(format!(
+                "Cannot update more than {} notes at once",
+                MAX_BULK_NOTES
+            )));
+        }
+        for tag in operations.add_tags.iter().chain(&operations.remove_tags) {
+            validate_tag_name(tag)?;
+        }
+        if operations.add_tags.iter().any(|added| {
+            operations
+                .remove_tags
+                .iter()
+                .any(|removed| removed.to_lowercase() == added.to_lowercase())
+        }) {
+            return Err(AppError::ValidationError(
+                "A tag cannot be added and removed at the same time".to_string(),
+            ));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        if let Some(Some(folder_id)) = operations.folder_id {
+            let exists: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM folders WHERE folder_id = $1 AND user_id = $2 AND is_deleted = FALSE)",
+            )
+            .bind(folder_id)
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if !exists {
+                return Err(AppError::NotFound("Folder not found".to_string()));
+            }
+        }
+
+        let mut add_tag_ids = Vec::with_capacity(operations.add_tags.len());
+        for tag_name in &operations.add_tags {
+            if let Some(tag) = self.get_or_create_tag(user_id, tag_name, &mut tx).await? {
+                add_tag_ids.push(tag.tag_id);
+            }
+        }
+
+        let remove_names: Vec<String> = operations
+            .remove_tags
+            .iter()
+            .map(|name| name.to_lowercase())
+            .collect();
+        let remove_tag_ids: Vec<i32> = sqlx::query_scalar(
+            "SELECT tag_id FROM tags WHERE user_id = $1 AND LOWER(name) = ANY($2)",
         )
         .bind(user_id)
-        .fetch_all(&self.pool)
+        .bind(&remove_names)
+        .fetch_all(&mut *tx)
         .await?;
 
-        let mut result = Vec::new();
-        for note in notes {
-            let with_relations = self.get_note_with_relations(note.note_id).await?;
-            result.push(with_relations);
+        let mut results = Vec::with_capacity(note_ids.len());
+        let mut trashed_titles = Vec::new();
+        let mut seen = Vec::with_capacity(note_ids.len());
+
+        for &note_id in note_ids {
+            if seen.contains(&note_id) {
+                continue;
+            }
+            seen.push(note_id);
+
+            let mut savepoint = tx.begin().await?;
+            let applied = apply_bulk_operations(
+                &mut savepoint,
+                user_id,
+                note_id,
+                operations,
+                &add_tag_ids,
+                &remove_tag_ids,
+            )
+            .await;
+
+            match applied {
+                Ok(title) => {
+                    savepoint.commit().await?;
+                    trashed_titles.extend(title);
+                    results.push(BulkNoteResult {
+                        note_id,
+                        success: true,
+                        error: None,
+                    });
+                }
+                Err(e) => {
+                    savepoint.rollback().await?;
+                    results.push(BulkNoteResult {
+                        note_id,
+                        success: false,
+                        error: Some(e.to_string()),
+                    });
+                }
+            }
         }
 
-        Ok(result)
+        // Links to trashed notes dangle until they are restored
+        if !trashed_titles.is_empty() {
+            LinkRepository::resolve_titles(&mut tx, user_id, &trashed_titles).await?;
+        }
+
+        tx.commit().await?;
+
+        let succeeded = results.iter().filter(|result| result.success).count();
+        Ok(BulkUpdateSummary {
+            failed: results.len() - succeeded,
+            succeeded,
+            results,
+        })
+    }
+
+    // Structured search (see `utils::search_query`) ranked against the weighted
+    // `search_vector` column. When nothing matches exactly the search is
+    // retried with fuzzy matching; the cursor remembers which mode produced
+    // a page so later pages continue the same way.
+    pub async fn search_notes(
+        &self,
+        user_id: i32,
+        search: &SearchExpr,
+        query: &NoteQuery,
+    ) -> Result<NotePage<SearchResult>> {
+        if query.decode_cursor()?.is_some_and(|cursor| cursor.fuzzy) {
+            return self.search(user_id, search, query, SearchMode::Fuzzy).await;
+        }
+
+        let page = self
+            .search(user_id, search, query, SearchMode::FullText)
+            .await?;
+
+        if page.items.is_empty() && query.cursor.is_none() && search.fuzzy_text().is_some() {
+            return self.search(user_id, search, query, SearchMode::Fuzzy).await;
+        }
+
+        Ok(page)
+    }
+
+    // Typo-tolerant search: words and phrases match by trigram similarity
+    // against the title and content instead of by stemmed lexemes, so near
+    // misses and partial identifiers are found. Filters work as in `search_notes`.
+    pub async fn fuzzy_search_notes(
+        &self,
+        user_id: i32,
+        search: &SearchExpr,
+        query: &NoteQuery,
+    ) -> Result<NotePage<SearchResult>> {
+        self.search(user_id, search, query, SearchMode::Fuzzy).await
+    }
+
+    // Number of notes a search matches, falling back to fuzzy matching the
+    // same way `search_notes` does so the count agrees with its first page
+    pub async fn count_search_results(
+        &self,
+        user_id: i32,
+        search: &SearchExpr,
+        query: &NoteQuery,
+    ) -> Result<i64> {
+        let count = self
+            .count_matches(user_id, search, query, SearchMode::FullText)
+            .await?;
+
+        if count == 0 && search.fuzzy_text().is_some() {
+            return self
+                .count_matches(user_id, search, query, SearchMode::Fuzzy)
+                .await;
+        }
+
+        Ok(count)
+    }
+
+    async fn count_matches(
+        &self,
+        user_id: i32,
+        search: &SearchExpr,
+        query: &NoteQuery,
+        mode: SearchMode,
+    ) -> Result<i64> {
+        let mut tx = self.pool.begin().await?;
+        if mode == SearchMode::Fuzzy {
+            set_fuzzy_thresholds(&mut tx).await?;
+        }
+
+        let mut count = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM notes n");
+        push_note_filters(&mut count, user_id, query, Some(NoteSearch { expr: search, mode }));
+        let total: i64 = count.build_query_scalar().fetch_one(&mut *tx).await?;
+
+        tx.commit().await?;
+        Ok(total)
     }
 
-    pub async fn get_archived_notes(&self, user_id: i32) -> Result<Vec<NoteWithRelations>> {
-        let notes = sqlx::query_as::<_, Note>(
-            "SELECT * FROM notes WHERE user_id = $1 AND is_archived = TRUE AND is_deleted = FALSE ORDER BY updated_at DESC"
+    // Each hit carries its rank and highlighted snippets of the title and content
+    async fn search(
+        &self,
+        user_id: i32,
+        search: &SearchExpr,
+        query: &NoteQuery,
+        mode: SearchMode,
+    ) -> Result<NotePage<SearchResult>> {
+        let page = self
+            .fetch_page(user_id, query, Some(NoteSearch { expr: search, mode }))
+            .await?;
+        let note_ids: Vec<i32> = page.items.iter().map(|r| r.note.note_id).collect();
+
+        let headlines = sqlx::query_as::<_, NoteHeadline>(
+            r#"
+            SELECT n.note_id,
+                   ts_headline('english', n.title, q.query, $3) AS title_headline,
+                   ts_headline('english', n.content, q.query, $4) AS content_headline
+            FROM notes n, websearch_to_tsquery('english', $2) AS q(query)
+            WHERE n.note_id = ANY($1)
+            "#,
         )
-        .bind(user_id)
+        .bind(&note_ids)
+        .bind(search.rank_text().unwrap_or_default())
+        .bind(TITLE_HEADLINE_OPTIONS)
+        .bind(CONTENT_HEADLINE_OPTIONS)
         .fetch_all(&self.pool)
         .await?;
 
-        let mut result = Vec::new();
-        for note in notes {
-            let with_relations = self.get_note_with_relations(note.note_id).await?;
-            result.push(with_relations);
+        let mut headlines: HashMap<i32, NoteHeadline> = headlines
+            .into_iter()
+            .map(|headline| (headline.note_id, headline))
+            .collect();
+
+        let scores: Vec<f32> = page.items.iter().map(|r| r.score).collect();
+        let notes = page.items.into_iter().map(|r| r.note).collect();
+        let notes = self.load_relations(notes).await?;
+
+        let items = notes
+            .into_iter()
+            .zip(scores)
+            .map(|(note, score)| {
+                let (title, content) = match headlines.remove(&note.note.note_id) {
+                    Some(headline) => (
+                        Snippet::from_headline(&headline.title_headline),
+                        Snippet::from_headline(&headline.content_headline),
+                    ),
+                    None => (Snippet::default(), Snippet::default()),
+                };
+                SearchResult {
+                    note,
+                    mode,
+                    score,
+                    title,
+                    content,
+                }
+            })
+            .collect();
+
+        Ok(NotePage {
+            items,
+            next_cursor: page.next_cursor,
+            total_estimate: page.total_estimate,
+        })
+    }
+
+    pub async fn get_notes_by_folder(
+        &self,
+        user_id: i32,
+        folder_id: i32,
+        query: &NoteQuery,
+    ) -> Result<NotePage> {
+        let query = NoteQuery {
+            folder_id: Some(folder_id),
+            ..query.clone()
+        };
+        self.list_notes(user_id, &query).await
+    }
+
+    pub async fn get_pinned_notes(&self, user_id: i32, query: &NoteQuery) -> Result<NotePage> {
+        let query = NoteQuery {
+            is_pinned: Some(true),
+            ..query.clone()
+        };
+        self.list_notes(user_id, &query).await
+    }
+
+    pub async fn get_archived_notes(&self, user_id: i32, query: &NoteQuery) -> Result<NotePage> {
+        let query = NoteQuery {
+            is_archived: Some(true),
+            ..query.clone()
+        };
+        self.list_notes(user_id, &query).await
+    }
+
+    // Live, unarchived notes matching a frontmatter property filter (see
+    // `utils::property_query`), optionally sorted by one property's value.
+    // Without a sort key the most recently edited notes come first.
+    pub async fn query_notes_by_properties(
+        &self,
+        user_id: i32,
+        query: &PropertyNoteQuery,
+    ) -> Result<Vec<NoteWithRelations>> {
+        let filter = match query.filter.as_deref().filter(|f| !f.trim().is_empty()) {
+            Some(filter) => Some(PropertyExpr::parse(filter)?),
+            None => None,
+        };
+        let sort_key = query.sort_by.as_deref().map(str::trim).filter(|k| !k.is_empty());
+
+        let mut builder = QueryBuilder::<Postgres>::new("SELECT n.* FROM notes n");
+        if let Some(key) = sort_key {
+            builder
+                .push(
+                    " LEFT JOIN note_properties sp \
+                     ON sp.note_id = n.note_id AND LOWER(sp.key) = LOWER(",
+                )
+                .push_bind(key.to_string())
+                .push(")");
         }
 
-        Ok(result)
+        builder
+            .push(" WHERE n.is_deleted = FALSE AND n.is_archived = FALSE AND n.user_id = ")
+            .push_bind(user_id);
+        if let Some(filter) = &filter {
+            builder.push(" AND ");
+            push_property_expr(&mut builder, filter);
+        }
+
+        if sort_key.is_some() {
+            let direction = query.direction.unwrap_or(SortDirection::Asc).keyword();
+            builder.push(format!(
+                " ORDER BY sp.number_value {0} NULLS LAST, sp.date_value {0} NULLS LAST, \
+                 LOWER(sp.text_value) {0} NULLS LAST, sp.bool_value {0} NULLS LAST, \
+                 n.updated_at DESC, n.note_id",
+                direction
+            ));
+        } else {
+            builder.push(" ORDER BY n.updated_at DESC, n.note_id");
+        }
+
+        let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+        builder.push(" LIMIT ").push_bind(limit as i64);
+
+        let notes = builder
+            .build_query_as::<Note>()
+            .fetch_all(&self.pool)
+            .await?;
+        self.load_relations(notes).await
+    }
+
+    async fn list_notes(&self, user_id: i32, query: &NoteQuery) -> Result<NotePage> {
+        if query.sort == NoteSortField::Relevance {
+            return Err(AppError::ValidationError(
+                "Relevance sorting is only available when searching".to_string(),
+            ));
+        }
+
+        let page = self.fetch_page(user_id, query, None).await?;
+        let notes = page.items.into_iter().map(|r| r.note).collect();
+
+        Ok(NotePage {
+            items: self.load_relations(notes).await?,
+            next_cursor: page.next_cursor,
+            total_estimate: page.total_estimate,
+        })
+    }
+
+    // Keyset-paginated page of notes shared by every listing and search. Rows
+    // are ordered by the requested sort key with the note id as a tie-breaker,
+    // and the cursor carries both so the next page starts right after the last row.
+    async fn fetch_page(
+        &self,
+        user_id: i32,
+        query: &NoteQuery,
+        search: Option<NoteSearch<'_>>,
+    ) -> Result<NotePage<RankedNote>> {
+        let cursor = query.decode_cursor()?;
+        let limit = query.limit() as usize;
+        let direction = query.direction.keyword();
+        let fuzzy = search.is_some_and(|search| search.mode == SearchMode::Fuzzy);
+
+        if cursor.as_ref().is_some_and(|cursor| cursor.fuzzy != fuzzy) {
+            return Err(AppError::ValidationError(
+                "Cursor does not match the requested search mode".to_string(),
+            ));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        if fuzzy {
+            set_fuzzy_thresholds(&mut tx).await?;
+        }
+
+        let mut builder = QueryBuilder::<Postgres>::new("SELECT n.*, ");
+        push_rank(&mut builder, search);
+        builder.push(" AS score FROM notes n");
+        push_note_filters(&mut builder, user_id, query, search);
+
+        let first_page = cursor.is_none();
+        if let Some(cursor) = cursor {
+            builder.push(" AND (");
+            push_sort_key(&mut builder, query.sort, search);
+            builder.push(format!(
+                ", n.note_id) {} (",
+                query.direction.after_operator()
+            ));
+            match cursor.value {
+                CursorValue::Timestamp(value) => builder.push_bind(value),
+                CursorValue::Text(value) => builder.push_bind(value),
+                CursorValue::Number(value) => builder.push_bind(value),
+                CursorValue::Score(value) => builder.push_bind(value),
+            };
+            builder.push(", ").push_bind(cursor.note_id).push(")");
+        }
+
+        builder.push(" ORDER BY ");
+        push_sort_key(&mut builder, query.sort, search);
+        builder.push(format!(" {}, n.note_id {} LIMIT ", direction, direction));
+        // Fetch one extra row to find out whether another page follows
+        builder.push_bind(limit as i64 + 1);
+
+        let mut notes = builder
+            .build_query_as::<RankedNote>()
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let next_cursor = if notes.len() > limit {
+            notes.truncate(limit);
+            notes.last().map(|r| {
+                let mut cursor =
+                    NoteCursor::for_note(&r.note, r.score, query.sort, query.direction);
+                cursor.fuzzy = fuzzy;
+                cursor.encode()
+            })
+        } else {
+            None
+        };
+
+        // Counting every match is as costly as the listing itself, so it is
+        // only done once, when the first page is requested
+        let total_estimate = if first_page {
+            let mut count = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM notes n");
+            push_note_filters(&mut count, user_id, query, search);
+            Some(count.build_query_scalar().fetch_one(&mut *tx).await?)
+        } else {
+            None
+        };
+
+        tx.commit().await?;
+
+        Ok(NotePage {
+            items: notes,
+            next_cursor,
+            total_estimate,
+        })
     }
 
     async fn get_note_with_relations(&self, note_id: i32) -> Result<NoteWithRelations> {
-        // Get note
         let note = sqlx::query_as::<_, Note>("SELECT * FROM notes WHERE note_id = $1")
             .bind(note_id)
             .fetch_one(&self.pool)
             .await?;
 
-        // Get folder if exists
-        let folder = if let Some(folder_id) = note.folder_id {
-            sqlx::query_as::<_, FolderInfo>(
-                "SELECT folder_id, name, color FROM folders WHERE folder_id = $1",
-            )
-            .bind(folder_id)
-            .fetch_optional(&self.pool)
-            .await?
-        } else {
-            None
-        };
+        let mut notes = self.load_relations(vec![note]).await?;
+        notes
+            .pop()
+            .ok_or_else(|| AppError::NotFound("Note not found".to_string()))
+    }
+
+    // Attach folder and tags to a batch of notes with a single query, keeping
+    // the order of the input so paginated listings stay sorted.
+    async fn load_relations(&self, notes: Vec<Note>) -> Result<Vec<NoteWithRelations>> {
+        if notes.is_empty() {
+            return Ok(Vec::new());
+        }
 
-        // Get tags
-        let tags = sqlx::query_as::<_, TagInfo>(
+        let note_ids: Vec<i32> = notes.iter().map(|note| note.note_id).collect();
+
+        let rows = sqlx::query_as::<_, NoteRelationsRow>(
             r#"
-            SELECT t.tag_id, t.name, t.color 
-            FROM tags t
-            INNER JOIN note_tags nt ON t.tag_id = nt.tag_id
-            WHERE nt.note_id = $1
+            SELECT n.note_id,
+                   f.folder_id, f.name AS folder_name, f.color AS folder_color,
+                   COALESCE(
+                       json_agg(
+                           json_build_object('tag_id', t.tag_id, 'name', t.name, 'color', t.color)
+                           ORDER BY t.name
+                       ) FILTER (WHERE t.tag_id IS NOT NULL),
+                       '[]'
+                   ) AS tags
+            FROM notes n
+            LEFT JOIN folders f ON f.folder_id = n.folder_id AND f.is_deleted = FALSE
+            LEFT JOIN note_tags nt ON nt.note_id = n.note_id
+            LEFT JOIN tags t ON t.tag_id = nt.tag_id AND t.is_deleted = FALSE
+            WHERE n.note_id = ANY($1)
+            GROUP BY n.note_id, f.folder_id
             "#,
         )
-        .bind(note_id)
+        .bind(&note_ids)
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(NoteWithRelations { note, folder, tags })
+        let mut relations: HashMap<i32, NoteRelationsRow> =
+            rows.into_iter().map(|row| (row.note_id, row)).collect();
+
+        let result = notes
+            .into_iter()
+            .map(|note| match relations.remove(&note.note_id) {
+                Some(row) => {
+                    let folder = match (row.folder_id, row.folder_name) {
+                        (Some(folder_id), Some(name)) => Some(FolderInfo {
+                            folder_id,
+                            name,
+                            color: row.folder_color,
+                        }),
+                        _ => None,
+                    };
+                    NoteWithRelations {
+                        note,
+                        folder,
+                        tags: row.tags.0,
+                    }
+                }
+                None => NoteWithRelations {
+                    note,
+                    folder: None,
+                    tags: Vec::new(),
+                },
+            })
+            .collect();
+
+        Ok(result)
     }
 
     async fn get_or_create_tag(
@@ -314,7 +1174,7 @@ impl NoteRepository {
     ) -> Result<Option<crate::database::models::tag::Tag>> {
         // Try to get existing tag
         let tag = sqlx::query_as::<_, crate::database::models::tag::Tag>(
-            "SELECT * FROM tags WHERE user_id = $1 AND LOWER(name) = LOWER($2)",
+            "SELECT * FROM tags WHERE user_id = $1 AND LOWER(name) = LOWER($2) AND is_deleted = FALSE",
         )
         .bind(user_id)
         .bind(tag_name)
@@ -338,6 +1198,385 @@ impl NoteRepository {
     }
 }
 
+// Apply bulk operations to one note. Returns the note's title when it was
+// moved to the trash.
+async fn apply_bulk_operations(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: i32,
+    note_id: i32,
+    operations: &BulkNoteOperations,
+    add_tag_ids: &[i32],
+    remove_tag_ids: &[i32],
+) -> Result<Option<String>> {
+    let title: String = sqlx::query_scalar(
+        "SELECT title FROM notes WHERE note_id = $1 AND user_id = $2 AND is_deleted = FALSE FOR UPDATE",
+    )
+    .bind(note_id)
+    .bind(user_id)
+    .fetch_optional(&mut **tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Note not found".to_string()))?;
+
+    let mut query = QueryBuilder::<Postgres>::new("UPDATE notes SET updated_at = ");
+    query.push_bind(Utc::now());
+
+    if let Some(folder_id) = operations.folder_id {
+        query.push(", folder_id = ").push_bind(folder_id);
+    }
+
+    if let Some(is_archived) = operations.is_archived {
+        query.push(", is_archived = ").push_bind(is_archived);
+    }
+
+    if let Some(is_pinned) = operations.is_pinned {
+        query.push(", is_pinned = ").push_bind(is_pinned);
+    }
+
+    if operations.delete {
+        query.push(", is_deleted = TRUE, deleted_at = NOW()");
+    }
+
+    query.push(" WHERE note_id = ").push_bind(note_id);
+    query.build().execute(&mut **tx).await?;
+
+    if !add_tag_ids.is_empty() {
+        sqlx::query(
+            "INSERT INTO note_tags (note_id, tag_id) SELECT $1, UNNEST($2::INTEGER[]) ON CONFLICT DO NOTHING",
+        )
+        .bind(note_id)
+        .bind(add_tag_ids)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    if !remove_tag_ids.is_empty() {
+        sqlx::query("DELETE FROM note_tags WHERE note_id = $1 AND tag_id = ANY($2)")
+            .bind(note_id)
+            .bind(remove_tag_ids)
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    Ok(operations.delete.then_some(title))
+}
+
+// Whether a live note in the folder (or at the top level) has this title
+async fn title_taken(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: i32,
+    folder_id: Option<i32>,
+    title: &str,
+) -> Result<bool> {
+    let taken: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM notes
+            WHERE user_id = $1 AND folder_id IS NOT DISTINCT FROM $2
+              AND title = $3 AND is_deleted = FALSE
+        )
+        "#,
+    )
+    .bind(user_id)
+    .bind(folder_id)
+    .bind(title)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(taken)
+}
+
+// The trigram operators compare against these settings rather than taking a threshold
+async fn set_fuzzy_thresholds(tx: &mut Transaction<'_, Postgres>) -> Result<()> {
+    sqlx::query(
+        "SELECT set_config('pg_trgm.similarity_threshold', $1, true), \
+         set_config('pg_trgm.word_similarity_threshold', $1, true)",
+    )
+    .bind(FUZZY_SIMILARITY_THRESHOLD.to_string())
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+fn push_note_filters(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    user_id: i32,
+    query: &NoteQuery,
+    search: Option<NoteSearch<'_>>,
+) {
+    builder
+        .push(" WHERE n.user_id = ")
+        .push_bind(user_id)
+        .push(" AND n.is_deleted = FALSE");
+
+    if let Some(folder_id) = query.folder_id {
+        builder.push(" AND n.folder_id = ").push_bind(folder_id);
+    }
+
+    if !query.tag_ids.is_empty() {
+        let mut tag_ids = query.tag_ids.clone();
+        tag_ids.sort_unstable();
+        tag_ids.dedup();
+        let tag_count = tag_ids.len() as i64;
+
+        builder
+            .push(" AND n.note_id IN (SELECT nt.note_id FROM note_tags nt WHERE nt.tag_id = ANY(")
+            .push_bind(tag_ids)
+            .push(") GROUP BY nt.note_id HAVING COUNT(*) = ")
+            .push_bind(tag_count)
+            .push(")");
+    }
+
+    if let Some(is_pinned) = query.is_pinned {
+        builder.push(" AND n.is_pinned = ").push_bind(is_pinned);
+    }
+
+    if let Some(is_archived) = query.is_archived {
+        builder.push(" AND n.is_archived = ").push_bind(is_archived);
+    }
+
+    if let Some(created_after) = query.created_after {
+        builder
+            .push(" AND n.created_at >= ")
+            .push_bind(created_after);
+    }
+
+    if let Some(created_before) = query.created_before {
+        builder
+            .push(" AND n.created_at < ")
+            .push_bind(created_before);
+    }
+
+    if let Some(updated_after) = query.updated_after {
+        builder
+            .push(" AND n.updated_at >= ")
+            .push_bind(updated_after);
+    }
+
+    if let Some(updated_before) = query.updated_before {
+        builder
+            .push(" AND n.updated_at < ")
+            .push_bind(updated_before);
+    }
+
+    if let Some(search) = search {
+        builder.push(" AND ");
+        push_search_expr(builder, user_id, search.expr, search.mode);
+    }
+}
+
+// Compile a parsed search query into a parameterized boolean SQL expression
+// over the notes alias `n`. Every term is parenthesized so `NOT` and the
+// AND/OR separators never need precedence rules of their own.
+fn push_search_expr(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    user_id: i32,
+    expr: &SearchExpr,
+    mode: SearchMode,
+) {
+    match expr {
+        SearchExpr::And(items) | SearchExpr::Or(items) => {
+            let separator = match expr {
+                SearchExpr::And(_) => " AND ",
+                _ => " OR ",
+            };
+            builder.push("(");
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    builder.push(separator);
+                }
+                push_search_expr(builder, user_id, item, mode);
+            }
+            builder.push(")");
+        }
+        SearchExpr::Not(inner) => {
+            builder.push("NOT ");
+            push_search_expr(builder, user_id, inner, mode);
+        }
+        SearchExpr::Term(term) => push_search_term(builder, user_id, term, mode),
+    }
+}
+
+fn push_search_term(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    user_id: i32,
+    term: &SearchTerm,
+    mode: SearchMode,
+) {
+    match term {
+        SearchTerm::Word(text) | SearchTerm::Phrase(text) if mode == SearchMode::Fuzzy => {
+            push_fuzzy_match(builder, text)
+        }
+        SearchTerm::Word(word) => push_text_match(builder, "plainto_tsquery", word),
+        SearchTerm::Phrase(phrase) => push_text_match(builder, "phraseto_tsquery", phrase),
+        SearchTerm::Tag(name) => {
+            builder
+                .push(
+                    "EXISTS (SELECT 1 FROM note_tags nt \
+                     INNER JOIN tags t ON t.tag_id = nt.tag_id \
+                     WHERE nt.note_id = n.note_id AND t.is_deleted = FALSE AND LOWER(t.name) = LOWER(",
+                )
+                .push_bind(name.clone())
+                .push("))");
+        }
+        SearchTerm::Folder(names) => {
+            // Build every live folder's path from the root as a list of names,
+            // match the requested names against its trailing names, then take
+            // in the subfolders
+            builder
+                .push(
+                    "(n.folder_id IS NOT NULL AND n.folder_id IN (\
+                     WITH RECURSIVE wanted AS (\
+                         SELECT ARRAY(\
+                             SELECT LOWER(name) FROM unnest(",
+                )
+                .push_bind(names.clone())
+                .push(
+                    "::TEXT[]) WITH ORDINALITY AS w(name, position) ORDER BY position\
+                         ) AS names\
+                     ), paths AS (\
+                         SELECT folder_id, ARRAY[LOWER(name)] AS path FROM folders \
+                         WHERE user_id = ",
+                )
+                .push_bind(user_id)
+                .push(
+                    " AND parent_folder_id IS NULL AND is_deleted = FALSE \
+                     UNION ALL \
+                     SELECT f.folder_id, p.path || LOWER(f.name) FROM folders f \
+                     INNER JOIN paths p ON f.parent_folder_id = p.folder_id \
+                     WHERE f.is_deleted = FALSE\
+                     ), matched AS (\
+                         SELECT p.folder_id FROM paths p, wanted w \
+                         WHERE p.path[cardinality(p.path) - cardinality(w.names) + 1:] = w.names\
+                     ), subtree AS (\
+                         SELECT folder_id FROM matched \
+                         UNION \
+                         SELECT f.folder_id FROM folders f \
+                         INNER JOIN subtree s ON f.parent_folder_id = s.folder_id \
+                         WHERE f.is_deleted = FALSE\
+                     ) SELECT folder_id FROM subtree))",
+                );
+        }
+        SearchTerm::Is(NoteFlag::Pinned) => {
+            builder.push("(n.is_pinned = TRUE)");
+        }
+        SearchTerm::Is(NoteFlag::Archived) => {
+            builder.push("(n.is_archived = TRUE)");
+        }
+        SearchTerm::Before(date) => {
+            builder
+                .push("(n.created_at < ")
+                .push_bind(date.and_time(NaiveTime::MIN).and_utc())
+                .push(")");
+        }
+        SearchTerm::After(date) => {
+            builder
+                .push("(n.created_at >= ")
+                .push_bind(date.and_time(NaiveTime::MIN).and_utc())
+                .push(")");
+        }
+    }
+}
+
+// Text that only holds stop words compiles to an empty tsquery, which would
+// match nothing; such terms are ignored instead of failing the whole search
+fn push_text_match(builder: &mut QueryBuilder<'_, Postgres>, function: &str, text: &str) {
+    builder
+        .push(format!("(n.search_vector @@ {}('english', ", function))
+        .push_bind(text.to_string())
+        .push(format!(") OR numnode({}('english', ", function))
+        .push_bind(text.to_string())
+        .push(")) = 0)");
+}
+
+// `<%` is true when the text closely matches some run of words in the column
+// and is backed by the trigram indexes
+fn push_fuzzy_match(builder: &mut QueryBuilder<'_, Postgres>, text: &str) {
+    builder
+        .push("(")
+        .push_bind(text.to_string())
+        .push(" <% n.title OR ")
+        .push_bind(text.to_string())
+        .push(" <% n.content)");
+}
+
+fn push_rank(builder: &mut QueryBuilder<'_, Postgres>, search: Option<NoteSearch<'_>>) {
+    let Some(search) = search else {
+        builder.push("0::REAL");
+        return;
+    };
+
+    match search.mode {
+        SearchMode::FullText => match search.expr.rank_text() {
+            Some(rank_text) => {
+                builder
+                    .push("ts_rank(n.search_vector, websearch_to_tsquery('english', ")
+                    .push_bind(rank_text)
+                    .push("))");
+            }
+            None => {
+                builder.push("0::REAL");
+            }
+        },
+        SearchMode::Fuzzy => match search.expr.fuzzy_text() {
+            Some(text) => {
+                builder
+                    .push("GREATEST(similarity(n.title, ")
+                    .push_bind(text.clone())
+                    .push("), word_similarity(")
+                    .push_bind(text.clone())
+                    .push(", n.title), word_similarity(")
+                    .push_bind(text)
+                    .push(", n.content))");
+            }
+            None => {
+                builder.push("0::REAL");
+            }
+        },
+    }
+}
+
+fn push_sort_key(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    sort: NoteSortField,
+    search: Option<NoteSearch<'_>>,
+) {
+    match sort {
+        NoteSortField::Created => builder.push("n.created_at"),
+        NoteSortField::Updated => builder.push("n.updated_at"),
+        NoteSortField::Title => builder.push("n.title"),
+        NoteSortField::Manual => builder.push("n.sort_order"),
+        NoteSortField::Relevance => {
+            push_rank(builder, search);
+            builder
+        }
+    };
+}
+
+// Minimum trigram similarity for fuzzy matches. Low enough that a swapped
+// pair of letters ("recieve") inside longer content still matches.
+const FUZZY_SIMILARITY_THRESHOLD: f32 = 0.3;
+
+// Upper bound on the notes touched by one bulk update
+const MAX_BULK_NOTES: usize = 1000;
+
+// Length of the `notes.title` column
+const MAX_TITLE_LENGTH: usize = 255;
+
+#[derive(Debug, Clone, Copy)]
+struct NoteSearch<'a> {
+    expr: &'a SearchExpr,
+    mode: SearchMode,
+}
+
+#[derive(Debug, FromRow)]
+struct NoteRelationsRow {
+    note_id: i32,
+    folder_id: Option<i32>,
+    folder_name: Option<String>,
+    folder_color: Option<String>,
+    tags: Json<Vec<TagInfo>>,
+}
+
 #[derive(Debug)]
 pub struct CreateNoteDto {
     pub user_id: i32,
@@ -345,6 +1584,9 @@ pub struct CreateNoteDto {
     pub content: String,
     pub folder_id: Option<i32>,
     pub is_pinned: bool,
+    // The template the note was rendered from, whose default folder and tags
+    // are applied on insert
+    pub template_id: Option<i32>,
 }
 
 #[derive(Debug)]
@@ -355,4 +1597,9 @@ pub struct UpdateNoteDto {
     pub folder_id: Option<Option<i32>>,
     pub is_pinned: Option<bool>,
     pub is_archived: Option<bool>,
+    pub sort_order: Option<i32>,
+    // On a rename, also rewrite `[[Old Title]]` links in the user's other notes
+    pub rewrite_links: bool,
+    // Reject the update with a conflict unless the note is still at this version
+    pub expected_version: Option<i32>,
 }
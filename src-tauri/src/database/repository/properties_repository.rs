@@ -0,0 +1,225 @@
+use super::super::models::note::Note;
+use super::super::models::property::{NoteProperty, PropertyKey};
+use crate::utils::error::Result;
+use crate::utils::frontmatter::{parse_frontmatter, PropertyValue};
+use crate::utils::property_query::{CompareOp, PropertyExpr, PropertyLiteral};
+use sqlx::{Pool, Postgres, QueryBuilder, Transaction};
+
+#[derive(Debug, Clone)]
+pub struct PropertyRepository {
+    pool: Pool<Postgres>,
+}
+
+impl PropertyRepository {
+    #[allow(dead_code)]
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Replace a note's properties with the ones in its current frontmatter.
+    // Runs inside the caller's transaction, next to the note write itself.
+    pub async fn sync_note_properties(
+        tx: &mut Transaction<'_, Postgres>,
+        note: &Note,
+    ) -> Result<()> {
+        sqlx::query("DELETE FROM note_properties WHERE note_id = $1")
+            .bind(note.note_id)
+            .execute(&mut **tx)
+            .await?;
+
+        for (key, value) in parse_frontmatter(&note.content) {
+            let mut query = QueryBuilder::<Postgres>::new(
+                "INSERT INTO note_properties (note_id, key, value_type, ",
+            );
+            query.push(match value {
+                PropertyValue::String(_) => "text_value",
+                PropertyValue::Number(_) => "number_value",
+                PropertyValue::Date(_) => "date_value",
+                PropertyValue::Boolean(_) => "bool_value",
+                PropertyValue::List(_) => "list_value",
+            });
+            query
+                .push(") VALUES (")
+                .push_bind(note.note_id)
+                .push(", ")
+                .push_bind(key)
+                .push(", ")
+                .push_bind(value.type_name())
+                .push(", ");
+            match value {
+                PropertyValue::String(text) => query.push_bind(text),
+                PropertyValue::Number(number) => query.push_bind(number),
+                PropertyValue::Date(date) => query.push_bind(date),
+                PropertyValue::Boolean(value) => query.push_bind(value),
+                PropertyValue::List(items) => query.push_bind(items),
+            };
+            query.push(")");
+
+            query.build().execute(&mut **tx).await?;
+        }
+
+        Ok(())
+    }
+
+    // Index notes saved before properties were tracked. Only notes that
+    // start with a frontmatter fence and have no properties yet are parsed.
+    pub async fn index_missing_properties(&self) -> Result<u64> {
+        let notes = sqlx::query_as::<_, Note>(
+            r#"
+            SELECT * FROM notes n
+            WHERE n.content ~ '^\uFEFF?---[ \t\r]*\n'
+              AND NOT EXISTS (SELECT 1 FROM note_properties p WHERE p.note_id = n.note_id)
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut tx = self.pool.begin().await?;
+        for note in &notes {
+            Self::sync_note_properties(&mut tx, note).await?;
+        }
+        tx.commit().await?;
+
+        Ok(notes.len() as u64)
+    }
+
+    pub async fn get_note_properties(
+        &self,
+        note_id: i32,
+        user_id: i32,
+    ) -> Result<Vec<NoteProperty>> {
+        let properties = sqlx::query_as::<_, NoteProperty>(
+            r#"
+            SELECT p.* FROM note_properties p
+            INNER JOIN notes n ON n.note_id = p.note_id
+            WHERE p.note_id = $1 AND n.user_id = $2
+            ORDER BY p.property_id
+            "#,
+        )
+        .bind(note_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(properties)
+    }
+
+    // Keys used in the user's live notes, most widely used first. Spellings
+    // that differ only in case are one key, shown in its most common form.
+    pub async fn list_property_keys(&self, user_id: i32) -> Result<Vec<PropertyKey>> {
+        let keys = sqlx::query_as::<_, PropertyKey>(
+            r#"
+            SELECT MODE() WITHIN GROUP (ORDER BY p.key)::TEXT AS key,
+                   ARRAY_AGG(DISTINCT p.value_type)::TEXT[] AS value_types,
+                   COUNT(*) AS note_count
+            FROM note_properties p
+            INNER JOIN notes n ON n.note_id = p.note_id
+            WHERE n.user_id = $1 AND n.is_deleted = FALSE
+            GROUP BY LOWER(p.key)
+            ORDER BY note_count DESC, LOWER(p.key)
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(keys)
+    }
+}
+
+// Compile a parsed property filter into a parameterized boolean SQL
+// expression over the notes alias `n`. A comparison holds when the note has
+// the property with a value of the literal's type that satisfies it; text
+// equality is case-insensitive and also matches any item of a list. `!=`
+// holds for notes that have the property with any other value.
+pub(crate) fn push_property_expr(builder: &mut QueryBuilder<'_, Postgres>, expr: &PropertyExpr) {
+    match expr {
+        PropertyExpr::And(items) | PropertyExpr::Or(items) => {
+            let separator = match expr {
+                PropertyExpr::And(_) => " AND ",
+                _ => " OR ",
+            };
+            builder.push("(");
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    builder.push(separator);
+                }
+                push_property_expr(builder, item);
+            }
+            builder.push(")");
+        }
+        PropertyExpr::Not(inner) => {
+            builder.push("NOT ");
+            push_property_expr(builder, inner);
+        }
+        PropertyExpr::Exists(key) => {
+            push_property_match(builder, key);
+            builder.push(")");
+        }
+        PropertyExpr::Compare {
+            key,
+            op: CompareOp::Ne,
+            value,
+        } => {
+            // The note has the property, just not with this value
+            builder.push("(");
+            push_property_expr(builder, &PropertyExpr::Exists(key.clone()));
+            builder.push(" AND NOT ");
+            push_property_expr(
+                builder,
+                &PropertyExpr::Compare {
+                    key: key.clone(),
+                    op: CompareOp::Eq,
+                    value: value.clone(),
+                },
+            );
+            builder.push(")");
+        }
+        PropertyExpr::Compare { key, op, value } => {
+            push_property_match(builder, key);
+            builder.push(" AND ");
+            match value {
+                PropertyLiteral::Text(text) if *op == CompareOp::Eq => {
+                    builder
+                        .push("(LOWER(p.text_value) = LOWER(")
+                        .push_bind(text.clone())
+                        .push(") OR LOWER(")
+                        .push_bind(text.clone())
+                        .push(") = ANY(SELECT LOWER(item) FROM UNNEST(p.list_value) AS item))");
+                }
+                PropertyLiteral::Text(text) => {
+                    builder
+                        .push(format!("p.text_value {} ", op.sql()))
+                        .push_bind(text.clone());
+                }
+                PropertyLiteral::Number(number) => {
+                    builder
+                        .push(format!("p.number_value {} ", op.sql()))
+                        .push_bind(*number);
+                }
+                PropertyLiteral::Date(date) => {
+                    builder
+                        .push(format!("p.date_value {} ", op.sql()))
+                        .push_bind(*date);
+                }
+                PropertyLiteral::Boolean(value) => {
+                    builder
+                        .push(format!("p.bool_value {} ", op.sql()))
+                        .push_bind(*value);
+                }
+            }
+            builder.push(")");
+        }
+    }
+}
+
+// Opens an `EXISTS (...` over the note's property named `key`; the caller closes it
+fn push_property_match(builder: &mut QueryBuilder<'_, Postgres>, key: &str) {
+    builder
+        .push(
+            "EXISTS (SELECT 1 FROM note_properties p \
+             WHERE p.note_id = n.note_id AND LOWER(p.key) = LOWER(",
+        )
+        .push_bind(key.to_string())
+        .push(")");
+}
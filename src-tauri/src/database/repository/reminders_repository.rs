@@ -0,0 +1,275 @@
+use super::super::models::reminder::{DueReminder, NoteReminder, Recurrence};
+use crate::utils::error::{AppError, Result};
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+
+#[derive(Debug, Clone)]
+pub struct ReminderRepository {
+    pool: Pool<Postgres>,
+}
+
+impl ReminderRepository {
+    #[allow(dead_code)]
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create_reminder(&self, dto: CreateReminderDto) -> Result<NoteReminder> {
+        validate_message(dto.message.as_deref())?;
+        self.check_note(dto.note_id, dto.user_id).await?;
+
+        let reminder = sqlx::query_as::<_, NoteReminder>(
+            r#"
+            INSERT INTO note_reminders (note_id, user_id, remind_at, recurrence, message)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+            "#,
+        )
+        .bind(dto.note_id)
+        .bind(dto.user_id)
+        .bind(dto.remind_at)
+        .bind(dto.recurrence)
+        .bind(clean_message(dto.message))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(reminder)
+    }
+
+    pub async fn get_note_reminders(
+        &self,
+        note_id: i32,
+        user_id: i32,
+    ) -> Result<Vec<NoteReminder>> {
+        self.check_note(note_id, user_id).await?;
+
+        let reminders = sqlx::query_as::<_, NoteReminder>(
+            r#"
+            SELECT * FROM note_reminders
+            WHERE note_id = $1 AND user_id = $2
+            ORDER BY is_done, remind_at
+            "#,
+        )
+        .bind(note_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(reminders)
+    }
+
+    // Pending reminders across all of the user's notes, soonest first
+    pub async fn get_upcoming_reminders(&self, user_id: i32) -> Result<Vec<DueReminder>> {
+        let reminders = sqlx::query_as::<_, DueReminder>(
+            r#"
+            SELECT r.reminder_id, r.note_id, n.title AS note_title, r.message,
+                   r.remind_at, r.recurrence, r.missed
+            FROM note_reminders r
+            JOIN notes n ON n.note_id = r.note_id
+            WHERE r.user_id = $1 AND r.is_done = FALSE AND n.is_deleted = FALSE
+            ORDER BY r.remind_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(reminders)
+    }
+
+    pub async fn update_reminder(&self, dto: UpdateReminderDto) -> Result<NoteReminder> {
+        if let Some(message) = &dto.message {
+            validate_message(message.as_deref())?;
+        }
+
+        // Moving a reminder to a new time reopens it unless the caller says otherwise
+        let is_done = dto.is_done.or(dto.remind_at.map(|_| false));
+
+        let reminder = sqlx::query_as::<_, NoteReminder>(
+            r#"
+            UPDATE note_reminders
+            SET remind_at = COALESCE($1, remind_at),
+                recurrence = CASE WHEN $2 THEN $3 ELSE recurrence END,
+                message = CASE WHEN $4 THEN $5 ELSE message END,
+                is_done = COALESCE($6, is_done),
+                updated_at = NOW()
+            WHERE reminder_id = $7 AND user_id = $8
+            RETURNING *
+            "#,
+        )
+        .bind(dto.remind_at)
+        .bind(dto.recurrence.is_some())
+        .bind(dto.recurrence.flatten())
+        .bind(dto.message.is_some())
+        .bind(dto.message.and_then(clean_message))
+        .bind(is_done)
+        .bind(dto.reminder_id)
+        .bind(dto.user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Reminder not found".to_string()))?;
+
+        Ok(reminder)
+    }
+
+    pub async fn delete_reminder(&self, reminder_id: i32, user_id: i32) -> Result<()> {
+        let result =
+            sqlx::query("DELETE FROM note_reminders WHERE reminder_id = $1 AND user_id = $2")
+                .bind(reminder_id)
+                .bind(user_id)
+                .execute(&self.pool)
+                .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Reminder not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    // Reminders that came due while the app was closed and are not dismissed yet
+    pub async fn get_missed_reminders(&self, user_id: i32) -> Result<Vec<DueReminder>> {
+        let reminders = sqlx::query_as::<_, DueReminder>(
+            r#"
+            SELECT r.reminder_id, r.note_id, n.title AS note_title, r.message,
+                   r.last_fired_at AS remind_at, r.recurrence, r.missed
+            FROM note_reminders r
+            JOIN notes n ON n.note_id = r.note_id
+            WHERE r.user_id = $1 AND r.missed = TRUE AND n.is_deleted = FALSE
+            ORDER BY r.last_fired_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(reminders)
+    }
+
+    pub async fn dismiss_missed_reminders(
+        &self,
+        reminder_ids: &[i32],
+        user_id: i32,
+    ) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE note_reminders SET missed = FALSE
+            WHERE reminder_id = ANY($1) AND user_id = $2 AND missed = TRUE
+            "#,
+        )
+        .bind(reminder_ids)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    // Fire every pending reminder due at `now`: one-off reminders are marked
+    // done and recurring ones move to their next occurrence after `now`, so a
+    // series that was due several times while the app was closed fires once.
+    // Rows are locked as they are claimed, so each occurrence fires exactly
+    // once even with several app instances running. `missed` marks reminders
+    // caught up on at startup; they stay listed until dismissed. Reminders on
+    // notes in the trash wait until the note is restored.
+    pub async fn claim_due_reminders(
+        &self,
+        now: DateTime<Utc>,
+        missed: bool,
+    ) -> Result<Vec<DueReminder>> {
+        let mut tx = self.pool.begin().await?;
+
+        let due = sqlx::query_as::<_, DueReminder>(
+            r#"
+            SELECT r.reminder_id, r.note_id, n.title AS note_title, r.message,
+                   r.remind_at, r.recurrence, $2 AS missed
+            FROM note_reminders r
+            JOIN notes n ON n.note_id = r.note_id
+            WHERE r.is_done = FALSE AND r.remind_at <= $1 AND n.is_deleted = FALSE
+            ORDER BY r.remind_at
+            FOR UPDATE OF r SKIP LOCKED
+            "#,
+        )
+        .bind(now)
+        .bind(missed)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for reminder in &due {
+            let next = reminder
+                .recurrence
+                .map(|recurrence| recurrence.next_after(reminder.remind_at, now));
+
+            sqlx::query(
+                r#"
+                UPDATE note_reminders
+                SET remind_at = COALESCE($1, remind_at),
+                    is_done = $1 IS NULL,
+                    missed = missed OR $2,
+                    last_fired_at = $3,
+                    updated_at = NOW()
+                WHERE reminder_id = $4
+                "#,
+            )
+            .bind(next)
+            .bind(missed)
+            .bind(reminder.remind_at)
+            .bind(reminder.reminder_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(due)
+    }
+
+    async fn check_note(&self, note_id: i32, user_id: i32) -> Result<()> {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM notes WHERE note_id = $1 AND user_id = $2 AND is_deleted = FALSE)",
+        )
+        .bind(note_id)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        if !exists {
+            return Err(AppError::NotFound("Note not found".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+fn clean_message(message: Option<String>) -> Option<String> {
+    message
+        .map(|message| message.trim().to_string())
+        .filter(|message| !message.is_empty())
+}
+
+fn validate_message(message: Option<&str>) -> Result<()> {
+    if message.is_some_and(|message| message.trim().len() > 255) {
+        return Err(AppError::ValidationError(
+            "Reminder message cannot exceed 255 characters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+pub struct CreateReminderDto {
+    pub note_id: i32,
+    pub user_id: i32,
+    pub remind_at: DateTime<Utc>,
+    pub recurrence: Option<Recurrence>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct UpdateReminderDto {
+    pub reminder_id: i32,
+    pub user_id: i32,
+    pub remind_at: Option<DateTime<Utc>>,
+    pub recurrence: Option<Option<Recurrence>>,
+    pub message: Option<Option<String>>,
+    pub is_done: Option<bool>,
+}
@@ -0,0 +1,117 @@
+use super::super::models::note::Note;
+use super::super::models::revision::{NoteRevision, NoteRevisionSummary};
+use crate::utils::error::Result;
+use sqlx::{Pool, Postgres, Transaction};
+
+#[derive(Debug, Clone)]
+pub struct RevisionRepository {
+    pool: Pool<Postgres>,
+}
+
+impl RevisionRepository {
+    #[allow(dead_code)]
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Snapshot a note before it is overwritten. Runs inside the caller's
+    // transaction so the revision and the update commit or roll back together.
+    pub async fn record_revision(
+        tx: &mut Transaction<'_, Postgres>,
+        note: &Note,
+    ) -> Result<NoteRevision> {
+        let revision = sqlx::query_as::<_, NoteRevision>(
+            r#"
+            INSERT INTO note_revisions (note_id, revision_number, title, content, version)
+            SELECT $1, COALESCE(MAX(revision_number), 0) + 1, $2, $3, $4
+            FROM note_revisions
+            WHERE note_id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(note.note_id)
+        .bind(&note.title)
+        .bind(&note.content)
+        .bind(note.version)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok(revision)
+    }
+
+    pub async fn get_note_history(
+        &self,
+        note_id: i32,
+        user_id: i32,
+    ) -> Result<Vec<NoteRevisionSummary>> {
+        let revisions = sqlx::query_as::<_, NoteRevisionSummary>(
+            r#"
+            SELECT r.revision_id, r.note_id, r.revision_number, r.title,
+                   LENGTH(r.content) AS content_length, r.created_at
+            FROM note_revisions r
+            INNER JOIN notes n ON n.note_id = r.note_id
+            WHERE r.note_id = $1 AND n.user_id = $2
+            ORDER BY r.revision_number DESC
+            "#,
+        )
+        .bind(note_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(revisions)
+    }
+
+    pub async fn get_revision(
+        &self,
+        revision_id: i32,
+        user_id: i32,
+    ) -> Result<Option<NoteRevision>> {
+        let revision = sqlx::query_as::<_, NoteRevision>(
+            r#"
+            SELECT r.*
+            FROM note_revisions r
+            INNER JOIN notes n ON n.note_id = r.note_id
+            WHERE r.revision_id = $1 AND n.user_id = $2
+            "#,
+        )
+        .bind(revision_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(revision)
+    }
+
+    // Keep at most `max_revisions` per note and drop anything older than
+    // `max_age_days`. A limit of 0 disables that half of the policy.
+    pub async fn prune_revisions(
+        &self,
+        note_id: i32,
+        max_revisions: u32,
+        max_age_days: u32,
+    ) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM note_revisions
+            WHERE note_id = $1
+            AND (
+                ($2 > 0 AND revision_id NOT IN (
+                    SELECT revision_id FROM note_revisions
+                    WHERE note_id = $1
+                    ORDER BY revision_number DESC
+                    LIMIT $2
+                ))
+                OR ($3 > 0 AND created_at < NOW() - make_interval(days => $3))
+            )
+            "#,
+        )
+        .bind(note_id)
+        .bind(max_revisions as i64)
+        .bind(max_age_days as i32)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
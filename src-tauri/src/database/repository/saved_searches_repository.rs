@@ -0,0 +1,187 @@
+use super::super::models::note_query::NoteQuery;
+use super::super::models::saved_search::SavedSearch;
+use super::trash_repository::name_taken;
+use crate::utils::error::{AppError, Result};
+use crate::utils::search_query::SearchExpr;
+use sqlx::types::Json;
+use sqlx::{Pool, Postgres};
+
+const NAME_TAKEN: &str = "A saved search with this name already exists";
+
+#[derive(Debug, Clone)]
+pub struct SavedSearchRepository {
+    pool: Pool<Postgres>,
+}
+
+impl SavedSearchRepository {
+    #[allow(dead_code)]
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create_saved_search(&self, dto: CreateSavedSearchDto) -> Result<SavedSearch> {
+        validate(&dto.name, &dto.query)?;
+
+        let search = sqlx::query_as::<_, SavedSearch>(
+            r#"
+            INSERT INTO saved_searches (user_id, name, query, options, is_pinned, sidebar_order)
+            VALUES ($1, $2, $3, $4, $5,
+                    (SELECT COALESCE(MAX(sidebar_order) + 1, 0) FROM saved_searches WHERE user_id = $1))
+            RETURNING *
+            "#,
+        )
+        .bind(dto.user_id)
+        .bind(dto.name.trim())
+        .bind(dto.query.trim())
+        .bind(Json(stored_options(dto.options)))
+        .bind(dto.is_pinned)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| name_taken(e, NAME_TAKEN))?;
+
+        Ok(search)
+    }
+
+    pub async fn get_saved_search(
+        &self,
+        saved_search_id: i32,
+        user_id: i32,
+    ) -> Result<Option<SavedSearch>> {
+        let search = sqlx::query_as::<_, SavedSearch>(
+            "SELECT * FROM saved_searches WHERE saved_search_id = $1 AND user_id = $2",
+        )
+        .bind(saved_search_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(search)
+    }
+
+    pub async fn list_saved_searches(&self, user_id: i32) -> Result<Vec<SavedSearch>> {
+        let searches = sqlx::query_as::<_, SavedSearch>(
+            "SELECT * FROM saved_searches WHERE user_id = $1 ORDER BY name",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(searches)
+    }
+
+    // Smart folders, in sidebar order
+    pub async fn get_pinned_searches(&self, user_id: i32) -> Result<Vec<SavedSearch>> {
+        let searches = sqlx::query_as::<_, SavedSearch>(
+            r#"
+            SELECT * FROM saved_searches
+            WHERE user_id = $1 AND is_pinned = TRUE
+            ORDER BY sidebar_order, name
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(searches)
+    }
+
+    pub async fn update_saved_search(&self, dto: UpdateSavedSearchDto) -> Result<SavedSearch> {
+        let current = self
+            .get_saved_search(dto.saved_search_id, dto.user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Saved search not found".to_string()))?;
+
+        let name = dto.name.unwrap_or(current.name);
+        let query = dto.query.unwrap_or(current.query);
+        let options = dto.options.map(stored_options).unwrap_or(current.options);
+
+        validate(&name, &query)?;
+
+        let search = sqlx::query_as::<_, SavedSearch>(
+            r#"
+            UPDATE saved_searches
+            SET name = $1, query = $2, options = $3,
+                is_pinned = COALESCE($4, is_pinned),
+                sidebar_order = COALESCE($5, sidebar_order),
+                updated_at = NOW()
+            WHERE saved_search_id = $6 AND user_id = $7
+            RETURNING *
+            "#,
+        )
+        .bind(name.trim())
+        .bind(query.trim())
+        .bind(Json(options))
+        .bind(dto.is_pinned)
+        .bind(dto.sidebar_order)
+        .bind(dto.saved_search_id)
+        .bind(dto.user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| name_taken(e, NAME_TAKEN))?
+        .ok_or_else(|| AppError::NotFound("Saved search not found".to_string()))?;
+
+        Ok(search)
+    }
+
+    pub async fn delete_saved_search(&self, saved_search_id: i32, user_id: i32) -> Result<()> {
+        let result =
+            sqlx::query("DELETE FROM saved_searches WHERE saved_search_id = $1 AND user_id = $2")
+                .bind(saved_search_id)
+                .bind(user_id)
+                .execute(&self.pool)
+                .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Saved search not found".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+// Paging belongs to each run of the search, not to the search itself
+fn stored_options(options: NoteQuery) -> NoteQuery {
+    NoteQuery {
+        cursor: None,
+        page_size: None,
+        ..options
+    }
+}
+
+fn validate(name: &str, query: &str) -> Result<()> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::ValidationError(
+            "Saved search name cannot be empty".to_string(),
+        ));
+    }
+    if name.chars().count() > 100 {
+        return Err(AppError::ValidationError(
+            "Saved search name cannot exceed 100 characters".to_string(),
+        ));
+    }
+
+    // Reject queries that would fail every time the search runs
+    SearchExpr::parse(query)?;
+    Ok(())
+}
+
+#[derive(Debug)]
+pub struct CreateSavedSearchDto {
+    pub user_id: i32,
+    pub name: String,
+    pub query: String,
+    pub options: NoteQuery,
+    pub is_pinned: bool,
+}
+
+#[derive(Debug)]
+pub struct UpdateSavedSearchDto {
+    pub saved_search_id: i32,
+    pub user_id: i32,
+    pub name: Option<String>,
+    pub query: Option<String>,
+    pub options: Option<NoteQuery>,
+    pub is_pinned: Option<bool>,
+    pub sidebar_order: Option<i32>,
+}
@@ -0,0 +1,229 @@
+use super::super::models::related::RelatedNote;
+use super::super::models::tag::{Tag, TagSuggestion};
+use crate::utils::error::{AppError, Result};
+use crate::utils::similarity::{term_counts, NoteMetadata, SimilarityIndex};
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, Pool, Postgres};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{Mutex, MutexGuard};
+
+pub const DEFAULT_RELATED_LIMIT: u32 = 10;
+pub const MAX_RELATED_LIMIT: u32 = 50;
+pub const DEFAULT_TAG_SUGGESTION_LIMIT: u32 = 5;
+pub const MAX_TAG_SUGGESTION_LIMIT: u32 = 20;
+
+// Content similarity between a user's notes, scored in-process. The index
+// for each user is kept between calls; every call re-reads which notes
+// exist and their folder and tags, but only tokenizes notes whose
+// `updated_at` moved since they were last indexed.
+#[derive(Debug, Clone)]
+pub struct SimilarityRepository {
+    pool: Pool<Postgres>,
+    indexes: Arc<Mutex<HashMap<i32, SimilarityIndex>>>,
+}
+
+impl SimilarityRepository {
+    #[allow(dead_code)]
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self {
+            pool,
+            indexes: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub async fn get_related_notes(
+        &self,
+        note_id: i32,
+        user_id: i32,
+        limit: Option<u32>,
+    ) -> Result<Vec<RelatedNote>> {
+        let limit = limit
+            .unwrap_or(DEFAULT_RELATED_LIMIT)
+            .clamp(1, MAX_RELATED_LIMIT) as usize;
+
+        let indexes = self.refreshed_indexes(user_id).await?;
+        let index = &indexes[&user_id];
+        if index.get(note_id).is_none() {
+            return Err(AppError::NotFound("Note not found".to_string()));
+        }
+
+        let related = index
+            .related(note_id, limit)
+            .into_iter()
+            .filter_map(|found| {
+                let note = index.get(found.note_id)?;
+                Some(RelatedNote {
+                    note_id: note.note_id,
+                    title: note.title.clone(),
+                    folder_id: note.folder_id,
+                    updated_at: note.updated_at,
+                    score: found.score,
+                    shared_terms: found.shared_terms,
+                    shared_tags: found.shared_tags,
+                    same_folder: found.same_folder,
+                })
+            })
+            .collect();
+
+        Ok(related)
+    }
+
+    // Existing tags that fit a note or an unsaved draft, best first
+    pub async fn suggest_tags(
+        &self,
+        user_id: i32,
+        source: TagSuggestionSource,
+        limit: Option<u32>,
+    ) -> Result<Vec<TagSuggestion>> {
+        let limit = limit
+            .unwrap_or(DEFAULT_TAG_SUGGESTION_LIMIT)
+            .clamp(1, MAX_TAG_SUGGESTION_LIMIT) as usize;
+
+        let tags = sqlx::query_as::<_, Tag>(
+            "SELECT * FROM tags WHERE user_id = $1 AND is_deleted = FALSE",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let vocabulary: Vec<String> = tags.iter().map(|tag| tag.name.clone()).collect();
+
+        let indexes = self.refreshed_indexes(user_id).await?;
+        let index = &indexes[&user_id];
+        let matches = match source {
+            TagSuggestionSource::Note(note_id) => {
+                let note = index
+                    .get(note_id)
+                    .ok_or_else(|| AppError::NotFound("Note not found".to_string()))?;
+                let terms = index.terms(note_id).cloned().unwrap_or_default();
+                index.suggest_tags(&terms, &note.tags, Some(note_id), &vocabulary, limit)
+            }
+            TagSuggestionSource::Draft {
+                title,
+                content,
+                applied_tags,
+            } => {
+                let terms = term_counts(&title, &content);
+                index.suggest_tags(&terms, &applied_tags, None, &vocabulary, limit)
+            }
+        };
+
+        let suggestions = matches
+            .into_iter()
+            .filter_map(|found| {
+                let tag = tags.iter().find(|tag| tag.name == found.name)?;
+                Some(TagSuggestion {
+                    tag_id: tag.tag_id,
+                    name: tag.name.clone(),
+                    color: tag.color.clone(),
+                    confidence: found.confidence,
+                    matched_terms: found.matched_terms,
+                    co_occurring_tags: found.co_occurring_tags,
+                    similar_notes: found.similar_notes,
+                })
+            })
+            .collect();
+
+        Ok(suggestions)
+    }
+
+    // Bring the user's index up to date and hand it out locked. The lock is
+    // held while changed notes are read, so concurrent callers wait for one
+    // refresh instead of repeating it.
+    pub(crate) async fn refreshed_indexes(
+        &self,
+        user_id: i32,
+    ) -> Result<MutexGuard<'_, HashMap<i32, SimilarityIndex>>> {
+        let live: Vec<NoteMetadata> = sqlx::query_as::<_, MetadataRow>(
+            r#"
+            SELECT n.note_id, n.folder_id, n.is_archived, n.updated_at,
+                   COALESCE(ARRAY_AGG(t.name ORDER BY t.name) FILTER (WHERE t.tag_id IS NOT NULL), '{}')
+                       AS tags
+            FROM notes n
+            LEFT JOIN note_tags nt ON nt.note_id = n.note_id
+            LEFT JOIN tags t ON t.tag_id = nt.tag_id AND t.is_deleted = FALSE
+            WHERE n.user_id = $1 AND n.is_deleted = FALSE
+            GROUP BY n.note_id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(MetadataRow::into_metadata)
+        .collect();
+
+        let mut indexes = self.indexes.lock().await;
+        let index = indexes.entry(user_id).or_default();
+        index.sync_metadata(&live);
+
+        let stale: HashMap<i32, NoteMetadata> = live
+            .into_iter()
+            .filter(|metadata| index.needs_text(metadata))
+            .map(|metadata| (metadata.note_id, metadata))
+            .collect();
+
+        if !stale.is_empty() {
+            let ids: Vec<i32> = stale.keys().copied().collect();
+            let texts = sqlx::query_as::<_, TextRow>(
+                "SELECT note_id, title, content, updated_at FROM notes WHERE note_id = ANY($1)",
+            )
+            .bind(&ids)
+            .fetch_all(&self.pool)
+            .await?;
+
+            for text in texts {
+                if let Some(metadata) = stale.get(&text.note_id) {
+                    // A save since the metadata was read is indexed with its own timestamp
+                    let metadata = NoteMetadata {
+                        updated_at: text.updated_at,
+                        ..metadata.clone()
+                    };
+                    index.upsert(metadata, text.title, &text.content);
+                }
+            }
+        }
+
+        Ok(indexes)
+    }
+}
+
+#[derive(Debug)]
+pub enum TagSuggestionSource {
+    Note(i32),
+    // Text that has not been saved yet, with the tags already chosen for it
+    Draft {
+        title: String,
+        content: String,
+        applied_tags: Vec<String>,
+    },
+}
+
+#[derive(Debug, FromRow)]
+struct MetadataRow {
+    note_id: i32,
+    folder_id: Option<i32>,
+    is_archived: bool,
+    updated_at: DateTime<Utc>,
+    tags: Vec<String>,
+}
+
+impl MetadataRow {
+    fn into_metadata(self) -> NoteMetadata {
+        NoteMetadata {
+            note_id: self.note_id,
+            folder_id: self.folder_id,
+            tags: self.tags,
+            is_archived: self.is_archived,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct TextRow {
+    note_id: i32,
+    title: String,
+    content: String,
+    updated_at: DateTime<Utc>,
+}
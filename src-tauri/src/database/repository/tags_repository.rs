@@ -32,10 +32,12 @@ impl TagRepository {
     }
 
     pub async fn get_tag_by_id(&self, tag_id: i32) -> Result<Option<TagWithNotes>> {
-        let tag = sqlx::query_as::<_, Tag>("SELECT * FROM tags WHERE tag_id = $1")
-            .bind(tag_id)
-            .fetch_optional(&self.pool)
-            .await?;
+        let tag = sqlx::query_as::<_, Tag>(
+            "SELECT * FROM tags WHERE tag_id = $1 AND is_deleted = FALSE",
+        )
+        .bind(tag_id)
+        .fetch_optional(&self.pool)
+        .await?;
 
         if let Some(tag) = tag {
             self.get_tag_with_notes(tag.tag_id).await.map(Some)
@@ -45,10 +47,12 @@ impl TagRepository {
     }
 
     pub async fn get_user_tags(&self, user_id: i32) -> Result<Vec<TagWithNotes>> {
-        let tags = sqlx::query_as::<_, Tag>("SELECT * FROM tags WHERE user_id = $1 ORDER BY name")
-            .bind(user_id)
-            .fetch_all(&self.pool)
-            .await?;
+        let tags = sqlx::query_as::<_, Tag>(
+            "SELECT * FROM tags WHERE user_id = $1 AND is_deleted = FALSE ORDER BY name",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
 
         let mut result = Vec::new();
         for tag in tags {
@@ -80,7 +84,10 @@ impl TagRepository {
             param_count += 1;
         }
 
-        query.push_str(&format!(" WHERE tag_id = ${} RETURNING *", param_count));
+        query.push_str(&format!(
+            " WHERE tag_id = ${} AND is_deleted = FALSE RETURNING *",
+            param_count
+        ));
         params.push(dto.tag_id.to_string());
 
         // Execute update
@@ -96,11 +103,14 @@ impl TagRepository {
     }
 
     pub async fn delete_tag(&self, tag_id: i32, user_id: i32) -> Result<()> {
-        sqlx::query("DELETE FROM tags WHERE tag_id = $1 AND user_id = $2")
-            .bind(tag_id)
-            .bind(user_id)
-            .execute(&self.pool)
-            .await?;
+        // Move the tag to the trash; its note associations are kept for a restore
+        sqlx::query(
+            "UPDATE tags SET is_deleted = TRUE, deleted_at = NOW() WHERE tag_id = $1 AND user_id = $2 AND is_deleted = FALSE"
+        )
+        .bind(tag_id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
 
         Ok(())
     }
@@ -0,0 +1,207 @@
+use super::super::models::note::Note;
+use super::super::models::task::{NoteTask, OpenTask, TaskFilter};
+use super::revisions_repository::RevisionRepository;
+use crate::utils::error::{AppError, Result};
+use crate::utils::tasks::{parse_tasks, toggle_task_line};
+use sqlx::{Pool, Postgres, QueryBuilder, Transaction};
+
+#[derive(Debug, Clone)]
+pub struct TaskRepository {
+    pool: Pool<Postgres>,
+}
+
+impl TaskRepository {
+    #[allow(dead_code)]
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Replace a note's tasks with the checkbox items in its current content.
+    // Runs inside the caller's transaction, next to the note write itself.
+    pub async fn sync_note_tasks(tx: &mut Transaction<'_, Postgres>, note: &Note) -> Result<()> {
+        sqlx::query("DELETE FROM note_tasks WHERE note_id = $1")
+            .bind(note.note_id)
+            .execute(&mut **tx)
+            .await?;
+
+        let tasks = parse_tasks(&note.content);
+        if tasks.is_empty() {
+            return Ok(());
+        }
+
+        let mut line_numbers = Vec::with_capacity(tasks.len());
+        let mut texts = Vec::with_capacity(tasks.len());
+        let mut done = Vec::with_capacity(tasks.len());
+        let mut due_dates = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            line_numbers.push(task.line_number as i32);
+            texts.push(task.text);
+            done.push(task.is_done);
+            due_dates.push(task.due_date);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO note_tasks (note_id, line_number, text, is_done, due_date)
+            SELECT $1, t.line_number, t.text, t.is_done, t.due_date
+            FROM UNNEST($2::INTEGER[], $3::TEXT[], $4::BOOLEAN[], $5::DATE[])
+                AS t(line_number, text, is_done, due_date)
+            "#,
+        )
+        .bind(note.note_id)
+        .bind(&line_numbers)
+        .bind(&texts)
+        .bind(&done)
+        .bind(&due_dates)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    // Index notes saved before tasks were tracked. Only notes that look like
+    // they contain a checkbox and have no tasks yet are parsed.
+    pub async fn index_missing_tasks(&self) -> Result<u64> {
+        let notes = sqlx::query_as::<_, Note>(
+            r#"
+            SELECT * FROM notes n
+            WHERE n.content ~ '\[[ xX]\]'
+              AND NOT EXISTS (SELECT 1 FROM note_tasks t WHERE t.note_id = n.note_id)
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut tx = self.pool.begin().await?;
+        for note in &notes {
+            Self::sync_note_tasks(&mut tx, note).await?;
+        }
+        tx.commit().await?;
+
+        Ok(notes.len() as u64)
+    }
+
+    pub async fn get_note_tasks(&self, note_id: i32, user_id: i32) -> Result<Vec<NoteTask>> {
+        let tasks = sqlx::query_as::<_, NoteTask>(
+            r#"
+            SELECT t.* FROM note_tasks t
+            INNER JOIN notes n ON n.note_id = t.note_id
+            WHERE t.note_id = $1 AND n.user_id = $2
+            ORDER BY t.line_number
+            "#,
+        )
+        .bind(note_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(tasks)
+    }
+
+    // Unchecked tasks across the user's live, unarchived notes: dated tasks
+    // first by due date, then the rest from the most recently edited notes
+    pub async fn get_open_tasks(&self, user_id: i32, filter: &TaskFilter) -> Result<Vec<OpenTask>> {
+        let mut query = QueryBuilder::<Postgres>::new(
+            r#"
+            SELECT t.task_id, t.note_id, n.title AS note_title, n.folder_id,
+                   t.line_number, t.text, t.due_date, n.updated_at AS note_updated_at
+            FROM note_tasks t
+            INNER JOIN notes n ON n.note_id = t.note_id
+            WHERE t.is_done = FALSE AND n.is_deleted = FALSE AND n.is_archived = FALSE
+              AND n.user_id = "#,
+        );
+        query.push_bind(user_id);
+
+        if let Some(folder_id) = filter.folder_id {
+            query
+                .push(
+                    " AND n.folder_id IN (\
+                     WITH RECURSIVE subtree AS (\
+                         SELECT folder_id FROM folders WHERE folder_id = ",
+                )
+                .push_bind(folder_id)
+                .push(" AND user_id = ")
+                .push_bind(user_id)
+                .push(
+                    " AND is_deleted = FALSE \
+                     UNION ALL \
+                     SELECT f.folder_id FROM folders f \
+                     INNER JOIN subtree s ON f.parent_folder_id = s.folder_id \
+                     WHERE f.is_deleted = FALSE\
+                     ) SELECT folder_id FROM subtree)",
+                );
+        }
+
+        if let Some(tag) = filter.tag.as_deref().filter(|tag| !tag.trim().is_empty()) {
+            query
+                .push(
+                    " AND EXISTS (SELECT 1 FROM note_tags nt \
+                     INNER JOIN tags tg ON tg.tag_id = nt.tag_id \
+                     WHERE nt.note_id = n.note_id AND tg.is_deleted = FALSE AND LOWER(tg.name) = LOWER(",
+                )
+                .push_bind(tag.trim())
+                .push("))");
+        }
+
+        query.push(
+            " ORDER BY t.due_date ASC NULLS LAST, n.updated_at DESC, t.note_id, t.line_number",
+        );
+
+        let tasks = query
+            .build_query_as::<OpenTask>()
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(tasks)
+    }
+
+    // Flip a task's checkbox in the note content. The note is locked while
+    // its content is rewritten, the previous content is kept as a revision
+    // and the task keeps its id. Task ids change whenever the note is saved,
+    // so a stale id is reported as not found.
+    pub async fn toggle_task(&self, task_id: i32, user_id: i32) -> Result<NoteTask> {
+        let mut tx = self.pool.begin().await?;
+
+        let note = sqlx::query_as::<_, Note>(
+            r#"
+            SELECT * FROM notes
+            WHERE note_id = (SELECT note_id FROM note_tasks WHERE task_id = $1)
+              AND user_id = $2 AND is_deleted = FALSE
+            FOR UPDATE
+            "#,
+        )
+        .bind(task_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Task not found".to_string()))?;
+
+        // A save that finished while we waited for the lock rebuilt the index
+        let task = sqlx::query_as::<_, NoteTask>("SELECT * FROM note_tasks WHERE task_id = $1")
+            .bind(task_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Task no longer exists in the note".to_string()))?;
+
+        let (content, is_done) = toggle_task_line(&note.content, task.line_number as usize)
+            .ok_or_else(|| AppError::NotFound("Task no longer exists in the note".to_string()))?;
+
+        RevisionRepository::record_revision(&mut tx, &note).await?;
+
+        sqlx::query("UPDATE notes SET content = $1, updated_at = NOW() WHERE note_id = $2")
+            .bind(&content)
+            .bind(note.note_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let task = sqlx::query_as::<_, NoteTask>(
+            "UPDATE note_tasks SET is_done = $1 WHERE task_id = $2 RETURNING *",
+        )
+        .bind(is_done)
+        .bind(task_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(task)
+    }
+}
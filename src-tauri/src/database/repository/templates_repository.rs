@@ -0,0 +1,222 @@
+use super::super::models::template::{NoteTemplate, TemplatePrompt};
+use super::trash_repository::name_taken;
+use crate::utils::error::{AppError, Result};
+use crate::utils::template::{validate_prompt_name, validate_template};
+use crate::utils::validation::validate_tag_name;
+use sqlx::types::Json;
+use sqlx::{Pool, Postgres};
+
+const NAME_TAKEN: &str = "A template with this name already exists";
+
+#[derive(Debug, Clone)]
+pub struct TemplateRepository {
+    pool: Pool<Postgres>,
+}
+
+impl TemplateRepository {
+    #[allow(dead_code)]
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create_template(&self, dto: CreateTemplateDto) -> Result<NoteTemplate> {
+        validate(
+            &dto.name,
+            &dto.title_template,
+            &dto.content,
+            &dto.default_tags,
+            &dto.prompts,
+        )?;
+        self.check_folder(dto.default_folder_id, dto.user_id)
+            .await?;
+
+        let template = sqlx::query_as::<_, NoteTemplate>(
+            r#"
+            INSERT INTO note_templates
+                (user_id, name, title_template, content, default_folder_id, default_tags, prompts)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+            "#,
+        )
+        .bind(dto.user_id)
+        .bind(dto.name.trim())
+        .bind(&dto.title_template)
+        .bind(&dto.content)
+        .bind(dto.default_folder_id)
+        .bind(&dto.default_tags)
+        .bind(Json(&dto.prompts))
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| name_taken(e, NAME_TAKEN))?;
+
+        Ok(template)
+    }
+
+    pub async fn get_template(
+        &self,
+        template_id: i32,
+        user_id: i32,
+    ) -> Result<Option<NoteTemplate>> {
+        let template = sqlx::query_as::<_, NoteTemplate>(
+            "SELECT * FROM note_templates WHERE template_id = $1 AND user_id = $2",
+        )
+        .bind(template_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(template)
+    }
+
+    pub async fn list_templates(&self, user_id: i32) -> Result<Vec<NoteTemplate>> {
+        let templates = sqlx::query_as::<_, NoteTemplate>(
+            "SELECT * FROM note_templates WHERE user_id = $1 ORDER BY name",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(templates)
+    }
+
+    pub async fn update_template(&self, dto: UpdateTemplateDto) -> Result<NoteTemplate> {
+        let current = self
+            .get_template(dto.template_id, dto.user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Template not found".to_string()))?;
+
+        // Placeholders are checked against the prompts as they will be after
+        // the update, so merge first and validate the result as a whole
+        let name = dto.name.unwrap_or(current.name);
+        let title_template = dto.title_template.unwrap_or(current.title_template);
+        let content = dto.content.unwrap_or(current.content);
+        let default_folder_id = dto.default_folder_id.unwrap_or(current.default_folder_id);
+        let default_tags = dto.default_tags.unwrap_or(current.default_tags);
+        let prompts = dto.prompts.unwrap_or(current.prompts);
+
+        validate(&name, &title_template, &content, &default_tags, &prompts)?;
+        self.check_folder(default_folder_id, dto.user_id).await?;
+
+        let template = sqlx::query_as::<_, NoteTemplate>(
+            r#"
+            UPDATE note_templates
+            SET name = $1, title_template = $2, content = $3, default_folder_id = $4,
+                default_tags = $5, prompts = $6, updated_at = NOW()
+            WHERE template_id = $7 AND user_id = $8
+            RETURNING *
+            "#,
+        )
+        .bind(name.trim())
+        .bind(&title_template)
+        .bind(&content)
+        .bind(default_folder_id)
+        .bind(&default_tags)
+        .bind(Json(&prompts))
+        .bind(dto.template_id)
+        .bind(dto.user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| name_taken(e, NAME_TAKEN))?
+        .ok_or_else(|| AppError::NotFound("Template not found".to_string()))?;
+
+        Ok(template)
+    }
+
+    pub async fn delete_template(&self, template_id: i32, user_id: i32) -> Result<()> {
+        let result =
+            sqlx::query("DELETE FROM note_templates WHERE template_id = $1 AND user_id = $2")
+                .bind(template_id)
+                .bind(user_id)
+                .execute(&self.pool)
+                .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Template not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn check_folder(&self, folder_id: Option<i32>, user_id: i32) -> Result<()> {
+        let Some(folder_id) = folder_id else {
+            return Ok(());
+        };
+
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM folders WHERE folder_id = $1 AND user_id = $2 AND is_deleted = FALSE)",
+        )
+        .bind(folder_id)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        if !exists {
+            return Err(AppError::NotFound("Default folder not found".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+fn validate(
+    name: &str,
+    title_template: &str,
+    content: &str,
+    default_tags: &[String],
+    prompts: &[TemplatePrompt],
+) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(AppError::ValidationError(
+            "Template name cannot be empty".to_string(),
+        ));
+    }
+    if name.len() > 100 {
+        return Err(AppError::ValidationError(
+            "Template name cannot exceed 100 characters".to_string(),
+        ));
+    }
+
+    let mut prompt_names: Vec<&str> = Vec::with_capacity(prompts.len());
+    for prompt in prompts {
+        validate_prompt_name(&prompt.name)?;
+        if prompt_names.contains(&prompt.name.as_str()) {
+            return Err(AppError::ValidationError(format!(
+                "Prompt '{}' is declared more than once",
+                prompt.name
+            )));
+        }
+        prompt_names.push(&prompt.name);
+    }
+
+    validate_template(title_template, &prompt_names)?;
+    validate_template(content, &prompt_names)?;
+
+    for tag in default_tags {
+        validate_tag_name(tag)?;
+    }
+
+    Ok(())
+}
+
+#[derive(Debug)]
+pub struct CreateTemplateDto {
+    pub user_id: i32,
+    pub name: String,
+    pub title_template: String,
+    pub content: String,
+    pub default_folder_id: Option<i32>,
+    pub default_tags: Vec<String>,
+    pub prompts: Vec<TemplatePrompt>,
+}
+
+#[derive(Debug)]
+pub struct UpdateTemplateDto {
+    pub template_id: i32,
+    pub user_id: i32,
+    pub name: Option<String>,
+    pub title_template: Option<String>,
+    pub content: Option<String>,
+    pub default_folder_id: Option<Option<i32>>,
+    pub default_tags: Option<Vec<String>>,
+    pub prompts: Option<Vec<TemplatePrompt>>,
+}
@@ -0,0 +1,394 @@
+use super::super::models::trash::{PurgeSummary, TrashItemType, TrashedItem, TrashedRow};
+use super::links_repository::LinkRepository;
+use crate::config::get_attachments_dir;
+use crate::utils::error::{AppError, Result};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{Pool, Postgres, Transaction};
+
+#[derive(Debug, Clone)]
+pub struct TrashRepository {
+    pool: Pool<Postgres>,
+}
+
+impl TrashRepository {
+    #[allow(dead_code)]
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get_trashed_items(
+        &self,
+        user_id: i32,
+        retention_days: u32,
+    ) -> Result<Vec<TrashedItem>> {
+        let notes = sqlx::query_as::<_, TrashedRow>(
+            r#"
+            SELECT note_id AS item_id, title AS name, folder_id, deleted_at
+            FROM notes
+            WHERE user_id = $1 AND is_deleted = TRUE
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let folders = sqlx::query_as::<_, TrashedRow>(
+            r#"
+            SELECT folder_id AS item_id, name, parent_folder_id AS folder_id, deleted_at
+            FROM folders
+            WHERE user_id = $1 AND is_deleted = TRUE
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let tags = sqlx::query_as::<_, TrashedRow>(
+            r#"
+            SELECT tag_id AS item_id, name, NULL::INTEGER AS folder_id, deleted_at
+            FROM tags
+            WHERE user_id = $1 AND is_deleted = TRUE
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let to_item = |item_type: TrashItemType, row: TrashedRow| TrashedItem {
+            item_type,
+            item_id: row.item_id,
+            name: row.name,
+            folder_id: row.folder_id,
+            purge_at: match (row.deleted_at, retention_days) {
+                (Some(deleted_at), days) if days > 0 => {
+                    Some(deleted_at + Duration::days(days as i64))
+                }
+                _ => None,
+            },
+            deleted_at: row.deleted_at,
+        };
+
+        // Items past their retention period are purged at startup; until then
+        // they are hidden rather than deleted here.
+        let now = Utc::now();
+        let mut items: Vec<TrashedItem> = notes
+            .into_iter()
+            .map(|row| to_item(TrashItemType::Note, row))
+            .chain(
+                folders
+                    .into_iter()
+                    .map(|row| to_item(TrashItemType::Folder, row)),
+            )
+            .chain(tags.into_iter().map(|row| to_item(TrashItemType::Tag, row)))
+            .filter(|item| item.purge_at.is_none_or(|purge_at| purge_at > now))
+            .collect();
+
+        // Most recently deleted first
+        items.sort_by_key(|item| std::cmp::Reverse(item.deleted_at));
+
+        Ok(items)
+    }
+
+    pub async fn restore_note(&self, note_id: i32, user_id: i32) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let restored: Option<(Option<i32>, String)> = sqlx::query_as(
+            r#"
+            UPDATE notes SET is_deleted = FALSE, deleted_at = NULL
+            WHERE note_id = $1 AND user_id = $2 AND is_deleted = TRUE
+            RETURNING folder_id, title
+            "#,
+        )
+        .bind(note_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| name_taken(e, "A daily note for this day already exists"))?;
+
+        let (folder_id, title) =
+            restored.ok_or_else(|| AppError::NotFound("Note not found in trash".to_string()))?;
+
+        // Links that dangled while the note was in the trash resolve again
+        LinkRepository::resolve_titles(&mut tx, user_id, &[title]).await?;
+
+        // Put the note back where it was, even if its folder was trashed too
+        if let Some(folder_id) = folder_id {
+            Self::restore_folder_chain(&mut tx, folder_id, user_id).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // Restore a folder along with the subfolders and notes that were trashed
+    // together with it; anything trashed separately before stays in the trash
+    pub async fn restore_folder(&self, folder_id: i32, user_id: i32) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let deleted_at: Option<DateTime<Utc>> = sqlx::query_scalar(
+            "SELECT deleted_at FROM folders WHERE folder_id = $1 AND user_id = $2 AND is_deleted = TRUE",
+        )
+        .bind(folder_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Folder not found in trash".to_string()))?;
+
+        Self::restore_folder_chain(&mut tx, folder_id, user_id).await?;
+        if let Some(deleted_at) = deleted_at {
+            Self::restore_folder_contents(&mut tx, folder_id, user_id, deleted_at).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn restore_tag(&self, tag_id: i32, user_id: i32) -> Result<()> {
+        // Note associations are kept while a tag is trashed, so restoring the
+        // tag puts it back on every note it was applied to.
+        let result = sqlx::query(
+            r#"
+            UPDATE tags SET is_deleted = FALSE, deleted_at = NULL
+            WHERE tag_id = $1 AND user_id = $2 AND is_deleted = TRUE
+            "#,
+        )
+        .bind(tag_id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| name_taken(e, "A tag with the same name already exists"))?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Tag not found in trash".to_string()));
+        }
+
+        Ok(())
+    }
+
+    pub async fn purge_note(&self, note_id: i32, user_id: i32) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let file_paths: Vec<String> =
+            sqlx::query_scalar("SELECT file_path FROM attachments WHERE note_id = $1")
+                .bind(note_id)
+                .fetch_all(&mut *tx)
+                .await?;
+
+        let result = sqlx::query(
+            "DELETE FROM notes WHERE note_id = $1 AND user_id = $2 AND is_deleted = TRUE",
+        )
+        .bind(note_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Note not found in trash".to_string()));
+        }
+
+        tx.commit().await?;
+        remove_files(&file_paths).await;
+
+        Ok(())
+    }
+
+    pub async fn empty_trash(&self, user_id: i32) -> Result<PurgeSummary> {
+        self.purge(Some(user_id), None).await
+    }
+
+    // Permanently delete everything that has been in the trash longer than
+    // `retention_days`, across all users. A retention of 0 disables purging.
+    pub async fn purge_expired(&self, retention_days: u32) -> Result<PurgeSummary> {
+        if retention_days == 0 {
+            return Ok(PurgeSummary::default());
+        }
+
+        self.purge(None, Some(retention_days as i32)).await
+    }
+
+    async fn purge(
+        &self,
+        user_id: Option<i32>,
+        older_than_days: Option<i32>,
+    ) -> Result<PurgeSummary> {
+        let mut tx = self.pool.begin().await?;
+
+        let file_paths: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT a.file_path
+            FROM attachments a
+            INNER JOIN notes n ON n.note_id = a.note_id
+            WHERE n.is_deleted = TRUE
+            AND ($1::INTEGER IS NULL OR n.user_id = $1)
+            AND ($2::INTEGER IS NULL OR n.deleted_at < NOW() - make_interval(days => $2))
+            "#,
+        )
+        .bind(user_id)
+        .bind(older_than_days)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let notes = sqlx::query(
+            r#"
+            DELETE FROM notes
+            WHERE is_deleted = TRUE
+            AND ($1::INTEGER IS NULL OR user_id = $1)
+            AND ($2::INTEGER IS NULL OR deleted_at < NOW() - make_interval(days => $2))
+            "#,
+        )
+        .bind(user_id)
+        .bind(older_than_days)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        let folders = sqlx::query(
+            r#"
+            DELETE FROM folders
+            WHERE is_deleted = TRUE
+            AND ($1::INTEGER IS NULL OR user_id = $1)
+            AND ($2::INTEGER IS NULL OR deleted_at < NOW() - make_interval(days => $2))
+            "#,
+        )
+        .bind(user_id)
+        .bind(older_than_days)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        let tags = sqlx::query(
+            r#"
+            DELETE FROM tags
+            WHERE is_deleted = TRUE
+            AND ($1::INTEGER IS NULL OR user_id = $1)
+            AND ($2::INTEGER IS NULL OR deleted_at < NOW() - make_interval(days => $2))
+            "#,
+        )
+        .bind(user_id)
+        .bind(older_than_days)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+        remove_files(&file_paths).await;
+
+        Ok(PurgeSummary {
+            notes,
+            folders,
+            tags,
+        })
+    }
+
+    // Restore a folder together with any trashed ancestors so it reappears at
+    // its original position in the tree. Returns the number of folders restored.
+    async fn restore_folder_chain(
+        tx: &mut Transaction<'_, Postgres>,
+        folder_id: i32,
+        user_id: i32,
+    ) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            WITH RECURSIVE chain AS (
+                SELECT folder_id, parent_folder_id
+                FROM folders
+                WHERE folder_id = $1 AND user_id = $2
+                UNION
+                SELECT f.folder_id, f.parent_folder_id
+                FROM folders f
+                INNER JOIN chain c ON f.folder_id = c.parent_folder_id
+            )
+            UPDATE folders SET is_deleted = FALSE, deleted_at = NULL
+            WHERE folder_id IN (SELECT folder_id FROM chain) AND is_deleted = TRUE
+            "#,
+        )
+        .bind(folder_id)
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| name_taken(e, "A folder with the same name already exists"))?;
+
+        Ok(result.rows_affected())
+    }
+
+    // Restore the subfolders and notes under `folder_id` that were trashed at
+    // `deleted_at`, i.e. in the same delete as the folder itself
+    async fn restore_folder_contents(
+        tx: &mut Transaction<'_, Postgres>,
+        folder_id: i32,
+        user_id: i32,
+        deleted_at: DateTime<Utc>,
+    ) -> Result<()> {
+        let mut folder_ids: Vec<i32> = sqlx::query_scalar(
+            r#"
+            WITH RECURSIVE batch AS (
+                SELECT folder_id FROM folders
+                WHERE parent_folder_id = $1 AND user_id = $2
+                  AND is_deleted = TRUE AND deleted_at = $3
+                UNION
+                SELECT f.folder_id FROM folders f
+                INNER JOIN batch b ON f.parent_folder_id = b.folder_id
+                WHERE f.is_deleted = TRUE AND f.deleted_at = $3
+            )
+            UPDATE folders SET is_deleted = FALSE, deleted_at = NULL
+            WHERE folder_id IN (SELECT folder_id FROM batch)
+            RETURNING folder_id
+            "#,
+        )
+        .bind(folder_id)
+        .bind(user_id)
+        .bind(deleted_at)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(|e| name_taken(e, "A folder with the same name already exists"))?;
+        folder_ids.push(folder_id);
+
+        let titles: Vec<String> = sqlx::query_scalar(
+            r#"
+            UPDATE notes SET is_deleted = FALSE, deleted_at = NULL
+            WHERE folder_id = ANY($1) AND user_id = $2
+              AND is_deleted = TRUE AND deleted_at = $3
+            RETURNING title
+            "#,
+        )
+        .bind(&folder_ids)
+        .bind(user_id)
+        .bind(deleted_at)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(|e| name_taken(e, "A daily note for this day already exists"))?;
+
+        // Links that dangled while the notes were in the trash resolve again
+        if !titles.is_empty() {
+            LinkRepository::resolve_titles(tx, user_id, &titles).await?;
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) fn name_taken(err: sqlx::Error, message: &str) -> AppError {
+    match &err {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            AppError::InvalidInput(message.to_string())
+        }
+        _ => err.into(),
+    }
+}
+
+// Only files the app copied into its own attachments directory are deleted.
+// Older attachments point at the user's original file, which must be left alone.
+pub(crate) async fn remove_files(paths: &[String]) {
+    let Ok(attachments_dir) = tokio::fs::canonicalize(get_attachments_dir()).await else {
+        return;
+    };
+
+    for path in paths {
+        match tokio::fs::canonicalize(path).await {
+            Ok(path) if path.starts_with(&attachments_dir) => {
+                let _ = tokio::fs::remove_file(path).await;
+            }
+            _ => {}
+        }
+    }
+}
@@ -2,6 +2,7 @@ pub mod commands;
 pub mod config;
 pub mod database;
 pub mod menu;
+pub mod scheduler;
 pub mod utils;
 
 // Re-exports for easier access
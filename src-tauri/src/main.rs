@@ -7,10 +7,16 @@ mod commands;
 mod config;
 mod database;
 mod menu;
+mod scheduler;
 mod utils;
 
 use commands::*;
+use config::settings::AppSettings;
 use database::init_db;
+use database::repository::properties_repository::PropertyRepository;
+use database::repository::reminders_repository::ReminderRepository;
+use database::repository::tasks_repository::TaskRepository;
+use database::repository::trash_repository::TrashRepository;
 use tauri::{Manager, generate_context};
 use tracing::info;
 
@@ -24,13 +30,37 @@ async fn main() {
     info!("Starting Recall Notes App...");
 
     tauri::Builder::default()
+        .plugin(tauri_plugin_notification::init())
         .setup(|app| {
-            let _handle = app.handle();
+            let handle = app.handle().clone();
 
             // Initialize database
             tokio::spawn(async move {
-                if let Err(e) = init_db().await {
-                    eprintln!("Failed to initialize database: {}", e);
+                match init_db().await {
+                    Ok(pool) => {
+                        // Purge trash items that outlived the retention period
+                        let settings = AppSettings::load();
+                        let trash = TrashRepository::new(pool.clone());
+                        if let Err(e) = trash.purge_expired(settings.trash_retention_days).await {
+                            eprintln!("Failed to purge expired trash: {}", e);
+                        }
+
+                        // Index checkbox tasks in notes saved before tasks were tracked
+                        let tasks = TaskRepository::new(pool.clone());
+                        if let Err(e) = tasks.index_missing_tasks().await {
+                            eprintln!("Failed to index note tasks: {}", e);
+                        }
+
+                        // Index frontmatter properties in notes saved before they were tracked
+                        let properties = PropertyRepository::new(pool.clone());
+                        if let Err(e) = properties.index_missing_properties().await {
+                            eprintln!("Failed to index note properties: {}", e);
+                        }
+
+                        // Fire note reminders, reporting any missed while the app was closed
+                        scheduler::start_reminder_scheduler(handle, ReminderRepository::new(pool));
+                    }
+                    Err(e) => eprintln!("Failed to initialize database: {}", e),
                 }
             });
 
@@ -47,18 +77,27 @@ async fn main() {
             get_all_notes,
             update_note,
             delete_note,
+            duplicate_note,
             search_notes,
+            fuzzy_search_notes,
             get_notes_by_folder,
             get_pinned_notes,
             get_archived_notes,
             toggle_note_pin,
             toggle_note_archive,
+            bulk_update_notes,
             // Folders commands
             create_folder,
             get_folder,
             get_all_folders,
             update_folder,
+            move_folder,
             delete_folder,
+            delete_folder_with_contents,
+            copy_folder,
+            resolve_folder_path,
+            ensure_folder_path,
+            get_folder_path,
             get_folder_tree,
             // Tags commands
             create_tag,
@@ -69,6 +108,7 @@ async fn main() {
             assign_tag_to_note,
             remove_tag_from_note,
             get_notes_by_tag,
+            suggest_tags,
             // User commands
             get_current_user,
             update_user_profile,
@@ -77,6 +117,59 @@ async fn main() {
             upload_attachment,
             delete_attachment,
             get_note_attachments,
+            // Note links
+            get_backlinks,
+            get_outgoing_links,
+            get_unresolved_links,
+            preview_link_rewrite,
+            // Revision history
+            get_note_history,
+            get_note_revision,
+            diff_note_revisions,
+            restore_note_revision,
+            // Trash
+            get_trashed_items,
+            restore_from_trash,
+            empty_trash,
+            purge_note_permanently,
+            // Templates
+            create_template,
+            list_templates,
+            update_template,
+            delete_template,
+            create_note_from_template,
+            // Journal
+            open_daily_note,
+            list_daily_notes,
+            get_adjacent_daily_notes,
+            // Reminders
+            create_reminder,
+            get_note_reminders,
+            get_upcoming_reminders,
+            update_reminder,
+            delete_reminder,
+            get_missed_reminders,
+            dismiss_missed_reminders,
+            parse_reminder_text,
+            // Tasks
+            get_open_tasks,
+            get_note_tasks,
+            toggle_task,
+            // Rendering
+            render_note_html,
+            export_note_html,
+            // Note properties
+            get_note_properties,
+            list_property_keys,
+            query_notes_by_properties,
+            // Saved searches
+            create_saved_search,
+            list_saved_searches,
+            update_saved_search,
+            delete_saved_search,
+            execute_saved_search,
+            // Related notes
+            get_related_notes,
         ])
         .menu(menu::build_menu)
         .on_menu_event(menu::handle_menu_event)
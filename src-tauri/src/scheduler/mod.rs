@@ -0,0 +1,4 @@
+pub mod reminders;
+
+#[allow(unused_imports)]
+pub use reminders::*;
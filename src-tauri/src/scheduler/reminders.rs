@@ -0,0 +1,65 @@
+use crate::database::models::reminder::DueReminder;
+use crate::database::repository::reminders_repository::ReminderRepository;
+use chrono::Utc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime};
+use tauri_plugin_notification::NotificationExt;
+
+// How often pending reminders are checked while the app is running
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+// Check for due reminders in the background for as long as the app runs.
+// Reminders that came due while the app was closed are reported once at
+// startup through a `reminder:missed` event and a single notification;
+// after that each reminder emits `reminder:due` and shows a notification
+// when it fires.
+pub fn start_reminder_scheduler<R: Runtime>(app: AppHandle<R>, reminders: ReminderRepository) {
+    tokio::spawn(async move {
+        match reminders.claim_due_reminders(Utc::now(), true).await {
+            Ok(missed) if !missed.is_empty() => report_missed(&app, &missed),
+            Ok(_) => {}
+            Err(e) => eprintln!("Failed to check missed reminders: {}", e),
+        }
+
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            match reminders.claim_due_reminders(Utc::now(), false).await {
+                Ok(due) => {
+                    for reminder in &due {
+                        fire(&app, reminder);
+                    }
+                }
+                Err(e) => eprintln!("Failed to check reminders: {}", e),
+            }
+        }
+    });
+}
+
+fn fire<R: Runtime>(app: &AppHandle<R>, reminder: &DueReminder) {
+    let _ = app.emit("reminder:due", reminder);
+    notify(
+        app,
+        &reminder.note_title,
+        reminder.message.as_deref().unwrap_or("Reminder"),
+    );
+}
+
+fn report_missed<R: Runtime>(app: &AppHandle<R>, missed: &[DueReminder]) {
+    let _ = app.emit("reminder:missed", missed);
+
+    let body = match missed {
+        [reminder] => format!("Missed reminder for \"{}\"", reminder.note_title),
+        _ => format!(
+            "{} reminders came due while Recall was closed",
+            missed.len()
+        ),
+    };
+    notify(app, "Missed reminders", &body);
+}
+
+fn notify<R: Runtime>(app: &AppHandle<R>, title: &str, body: &str) {
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        eprintln!("Failed to show notification: {}", e);
+    }
+}
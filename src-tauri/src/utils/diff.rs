@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffGranularity {
+    #[default]
+    Line,
+    Word,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffChange {
+    pub op: DiffOp,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextDiffResult {
+    pub changes: Vec<DiffChange>,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+pub fn diff_text(old: &str, new: &str, granularity: DiffGranularity) -> TextDiffResult {
+    let diff = match granularity {
+        DiffGranularity::Line => TextDiff::from_lines(old, new),
+        DiffGranularity::Word => TextDiff::from_words(old, new),
+    };
+
+    let mut changes: Vec<DiffChange> = Vec::new();
+    let mut insertions = 0;
+    let mut deletions = 0;
+
+    for change in diff.iter_all_changes() {
+        let op = match change.tag() {
+            ChangeTag::Equal => DiffOp::Equal,
+            ChangeTag::Insert => {
+                insertions += 1;
+                DiffOp::Insert
+            }
+            ChangeTag::Delete => {
+                deletions += 1;
+                DiffOp::Delete
+            }
+        };
+
+        // Merge runs of the same operation so the frontend gets whole blocks
+        match changes.last_mut() {
+            Some(last) if last.op == op => last.value.push_str(change.value()),
+            _ => changes.push(DiffChange {
+                op,
+                value: change.value().to_string(),
+            }),
+        }
+    }
+
+    TextDiffResult {
+        changes,
+        insertions,
+        deletions,
+    }
+}
@@ -1,3 +1,4 @@
+use crate::database::models::note::NoteConflict;
 use serde::Serialize;
 use thiserror::Error;
 
@@ -15,6 +16,13 @@ pub enum AppError {
     #[error("Invalid input: {0}")]
     InvalidInput(String),
 
+    // The request was based on data that has changed since it was read
+    #[error("Conflict: {message}")]
+    Conflict {
+        message: String,
+        details: Option<Box<NoteConflict>>,
+    },
+
     #[allow(dead_code)]
     #[error("Unauthorized")]
     Unauthorized,
@@ -0,0 +1,61 @@
+use crate::utils::error::{AppError, Result};
+use crate::utils::validation::validate_folder_name;
+
+// Folder paths name a folder by the chain of folders leading to it, e.g.
+// `Work/Clients/Acme`:
+//
+// - `/` separates folder names; a leading or trailing `/` is ignored
+// - `\/` is a `/` that belongs to the name and `\\` is a backslash, so the
+//   folder "Q1/Q2" under "Reports" is `Reports/Q1\/Q2`
+// - Names are trimmed, and an empty name (as in `Work//Acme`) is an error
+//
+// Lookups ignore case, so `work/clients` finds "Work/Clients". When siblings
+// differ only in case, the exact spelling wins, then the oldest folder.
+
+pub fn parse_folder_path(path: &str) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    let mut current = String::new();
+    let mut chars = path.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped @ ('/' | '\\')) => current.push(escaped),
+                _ => {
+                    return Err(AppError::ValidationError(
+                        "A backslash in a folder path must be followed by / or \\".to_string(),
+                    ))
+                }
+            },
+            '/' => names.push(std::mem::take(&mut current).trim().to_string()),
+            _ => current.push(c),
+        }
+    }
+    names.push(current.trim().to_string());
+
+    // Slashes at either end do not create empty names
+    if names.len() > 1 && names[0].is_empty() {
+        names.remove(0);
+    }
+    if names.len() > 1 && names[names.len() - 1].is_empty() {
+        names.pop();
+    }
+    if names.iter().all(|name| name.is_empty()) {
+        return Err(AppError::ValidationError(
+            "Folder path cannot be empty".to_string(),
+        ));
+    }
+    for name in &names {
+        validate_folder_name(name)?;
+    }
+
+    Ok(names)
+}
+
+pub fn format_folder_path<S: AsRef<str>>(names: &[S]) -> String {
+    names
+        .iter()
+        .map(|name| name.as_ref().replace('\\', "\\\\").replace('/', "\\/"))
+        .collect::<Vec<_>>()
+        .join("/")
+}
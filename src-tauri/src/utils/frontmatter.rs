@@ -0,0 +1,217 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+// YAML frontmatter at the very top of a note:
+//
+//   ---
+//   status: draft
+//   priority: 2
+//   due: 2026-11-01
+//   aliases: [roadmap, plans]
+//   ---
+//
+// Top-level keys become typed properties. Strings in `YYYY-MM-DD` form are
+// dates, sequences become lists of their scalar items, and null or nested
+// mapping values are skipped. Keys are matched case-insensitively, so only
+// the first spelling of a repeated key is kept. Frontmatter that is not a
+// valid YAML mapping yields no properties.
+
+pub const MAX_PROPERTY_KEY_LENGTH: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum PropertyValue {
+    String(String),
+    Number(f64),
+    Date(NaiveDate),
+    Boolean(bool),
+    List(Vec<String>),
+}
+
+impl PropertyValue {
+    // Stored in `note_properties.value_type`
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            PropertyValue::String(_) => "string",
+            PropertyValue::Number(_) => "number",
+            PropertyValue::Date(_) => "date",
+            PropertyValue::Boolean(_) => "boolean",
+            PropertyValue::List(_) => "list",
+        }
+    }
+}
+
+// The YAML between the opening and closing `---` lines, if the content starts with one
+pub fn frontmatter_block(content: &str) -> Option<&str> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let (first, rest) = content.split_once('\n')?;
+    if first.trim_end() != "---" {
+        return None;
+    }
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed == "..." {
+            return Some(&rest[..offset]);
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+pub fn parse_frontmatter(content: &str) -> Vec<(String, PropertyValue)> {
+    let Some(block) = frontmatter_block(content) else {
+        return Vec::new();
+    };
+    let Ok(Value::Mapping(mapping)) = serde_yaml::from_str::<Value>(block) else {
+        return Vec::new();
+    };
+
+    let mut properties: Vec<(String, PropertyValue)> = Vec::new();
+    for (key, value) in mapping {
+        let Some(key) = scalar_text(&key) else {
+            continue;
+        };
+        let key = key.trim().to_string();
+        if key.is_empty() || key.chars().count() > MAX_PROPERTY_KEY_LENGTH {
+            continue;
+        }
+        if properties
+            .iter()
+            .any(|(existing, _)| existing.to_lowercase() == key.to_lowercase())
+        {
+            continue;
+        }
+        if let Some(value) = property_value(value) {
+            properties.push((key, value));
+        }
+    }
+
+    properties
+}
+
+fn property_value(value: Value) -> Option<PropertyValue> {
+    match value {
+        Value::Bool(value) => Some(PropertyValue::Boolean(value)),
+        Value::Number(number) => number
+            .as_f64()
+            .filter(|number| number.is_finite())
+            .map(PropertyValue::Number),
+        Value::String(text) => Some(match NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d") {
+            Ok(date) => PropertyValue::Date(date),
+            Err(_) => PropertyValue::String(text),
+        }),
+        Value::Sequence(items) => Some(PropertyValue::List(
+            items.iter().filter_map(scalar_text).collect(),
+        )),
+        Value::Tagged(tagged) => property_value(tagged.value),
+        Value::Null | Value::Mapping(_) => None,
+    }
+}
+
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_boundaries() {
+        assert_eq!(frontmatter_block("---\na: 1\n---\nbody"), Some("a: 1\n"));
+        assert_eq!(
+            frontmatter_block("\u{feff}---\r\na: 1\r\n...\r\n"),
+            Some("a: 1\r\n")
+        );
+        assert_eq!(frontmatter_block("---\n---\n"), Some(""));
+        // Must open on the first line and be closed
+        assert_eq!(frontmatter_block("\n---\na: 1\n---\n"), None);
+        assert_eq!(frontmatter_block("---\na: 1\n"), None);
+        assert_eq!(frontmatter_block("--- \na: 1\n---"), Some("a: 1\n"));
+        assert_eq!(frontmatter_block("----\na: 1\n---\n"), None);
+    }
+
+    #[test]
+    fn typed_values() {
+        let content = "\
+---
+status: draft
+priority: 2
+score: 1.5
+done: false
+due: 2026-11-01
+quoted_due: \"2026-11-01\"
+not_a_date: 2026-02-30
+aliases: [roadmap, 3, true, {nested: x}]
+empty:
+nested:
+  key: value
+tagged: !custom 7
+---
+body
+";
+        assert_eq!(
+            parse_frontmatter(content),
+            vec![
+                (
+                    "status".to_string(),
+                    PropertyValue::String("draft".to_string())
+                ),
+                ("priority".to_string(), PropertyValue::Number(2.0)),
+                ("score".to_string(), PropertyValue::Number(1.5)),
+                ("done".to_string(), PropertyValue::Boolean(false)),
+                (
+                    "due".to_string(),
+                    PropertyValue::Date(NaiveDate::from_ymd_opt(2026, 11, 1).unwrap())
+                ),
+                (
+                    "quoted_due".to_string(),
+                    PropertyValue::Date(NaiveDate::from_ymd_opt(2026, 11, 1).unwrap())
+                ),
+                (
+                    "not_a_date".to_string(),
+                    PropertyValue::String("2026-02-30".to_string())
+                ),
+                (
+                    "aliases".to_string(),
+                    PropertyValue::List(vec![
+                        "roadmap".to_string(),
+                        "3".to_string(),
+                        "true".to_string()
+                    ])
+                ),
+                ("tagged".to_string(), PropertyValue::Number(7.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn keys() {
+        let long = "k".repeat(MAX_PROPERTY_KEY_LENGTH + 1);
+        let content = format!("---\nStatus : a\nstatus: b\n1: one\n{long}: x\n\"\": y\n---\n");
+        assert_eq!(
+            parse_frontmatter(&content),
+            vec![
+                ("Status".to_string(), PropertyValue::String("a".to_string())),
+                ("1".to_string(), PropertyValue::String("one".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_frontmatter_yields_nothing() {
+        assert!(parse_frontmatter("no frontmatter").is_empty());
+        assert!(parse_frontmatter("---\n- a\n- b\n---\n").is_empty());
+        assert!(parse_frontmatter("---\nkey: [unclosed\n---\n").is_empty());
+        assert!(parse_frontmatter("---\njust text\n---\n").is_empty());
+    }
+}
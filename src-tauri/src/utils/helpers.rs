@@ -36,6 +36,21 @@ pub fn generate_slug(text: &str) -> String {
         .join("-")
 }
 
+// The name for the `attempt`-th copy of something: "Name (copy)", then
+// "Name (copy 2)" and so on, shortened to fit in `max_chars`
+pub fn copy_name(name: &str, attempt: u32, max_chars: usize) -> String {
+    let suffix = if attempt <= 1 {
+        " (copy)".to_string()
+    } else {
+        format!(" (copy {})", attempt)
+    };
+    let base: String = name
+        .chars()
+        .take(max_chars.saturating_sub(suffix.chars().count()))
+        .collect();
+    format!("{}{}", base.trim_end(), suffix)
+}
+
 #[allow(dead_code)]
 pub fn sanitize_filename(filename: &str) -> String {
     filename
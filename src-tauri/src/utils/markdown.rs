@@ -0,0 +1,293 @@
+use crate::database::models::attachment::Attachment;
+use crate::utils::error::{AppError, Result};
+use lazy_static::lazy_static;
+use pulldown_cmark::{html, CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use syntect::highlighting::ThemeSet;
+use syntect::html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+// CommonMark with GFM tables, task lists, strikethrough and footnotes,
+// rendered to HTML that is safe to inject into the webview. Raw HTML in a
+// note is kept but sanitized; highlighted code uses `hl-` prefixed classes
+// styled by `RenderedHtml::highlight_css`.
+//
+// Attachments are referenced as `attachment:<id>`, `attachment:<file name>`
+// or by their bare file name, in links and images alike.
+
+lazy_static! {
+    static ref SYNTAXES: SyntaxSet = SyntaxSet::load_defaults_newlines();
+    static ref THEMES: ThemeSet = ThemeSet::load_defaults();
+}
+
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+const ATTACHMENT_SCHEME: &str = "attachment:";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderOptions {
+    pub highlight_code: bool,
+    // One of syntect's bundled themes, e.g. "InspiredGitHub" or "base16-ocean.dark"
+    pub theme: String,
+    pub attachment_links: AttachmentLinks,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            highlight_code: true,
+            theme: "InspiredGitHub".to_string(),
+            attachment_links: AttachmentLinks::Asset,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentLinks {
+    // Tauri's asset protocol, for display inside the app
+    #[default]
+    Asset,
+    // `file://` URLs, for HTML written to disk or printed
+    File,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedHtml {
+    pub html: String,
+    // Stylesheet for highlighted code blocks, when there are any
+    pub highlight_css: Option<String>,
+    // Attachments the content links to
+    pub attachment_ids: Vec<i32>,
+}
+
+impl RenderedHtml {
+    // A standalone page for export and printing
+    pub fn to_document(&self, title: &str) -> String {
+        let mut document = String::with_capacity(self.html.len() + 256);
+        document.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        let _ = writeln!(document, "<title>{}</title>", ammonia::clean_text(title));
+        if let Some(css) = &self.highlight_css {
+            let _ = writeln!(document, "<style>\n{}</style>", css);
+        }
+        document.push_str("</head>\n<body>\n");
+        document.push_str(&self.html);
+        document.push_str("</body>\n</html>\n");
+        document
+    }
+}
+
+pub fn render_markdown(
+    content: &str,
+    options: &RenderOptions,
+    attachments: &[Attachment],
+) -> Result<RenderedHtml> {
+    let theme = THEMES.themes.get(&options.theme).ok_or_else(|| {
+        AppError::ValidationError(format!("Unknown highlight theme '{}'", options.theme))
+    })?;
+
+    let parser_options = Options::ENABLE_TABLES
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_FOOTNOTES;
+
+    let mut events = Vec::new();
+    let mut attachment_ids = Vec::new();
+    let mut code: Option<(String, String)> = None;
+    let mut highlighted = false;
+
+    for event in Parser::new_ext(content, parser_options) {
+        // Collect fenced code so it can be highlighted as a whole
+        if let Some((language, text)) = code.as_mut() {
+            match event {
+                Event::Text(chunk) => text.push_str(&chunk),
+                Event::End(TagEnd::CodeBlock) => {
+                    events.push(Event::Html(CowStr::from(highlight(language, text)?)));
+                    highlighted = true;
+                    code = None;
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let event = match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) if options.highlight_code => {
+                let language = info.split_whitespace().next().unwrap_or_default();
+                code = Some((language.to_string(), String::new()));
+                continue;
+            }
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => Event::Start(Tag::Link {
+                link_type,
+                dest_url: rewrite_url(dest_url, options, attachments, &mut attachment_ids),
+                title,
+                id,
+            }),
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => Event::Start(Tag::Image {
+                link_type,
+                dest_url: rewrite_url(dest_url, options, attachments, &mut attachment_ids),
+                title,
+                id,
+            }),
+            event => event,
+        };
+        events.push(event);
+    }
+
+    let mut unsafe_html = String::with_capacity(content.len() * 3 / 2);
+    html::push_html(&mut unsafe_html, events.into_iter());
+
+    let highlight_css = if highlighted {
+        Some(
+            css_for_theme_with_class_style(theme, CLASS_STYLE).map_err(|e| {
+                AppError::ValidationError(format!("Cannot use theme '{}': {}", options.theme, e))
+            })?,
+        )
+    } else {
+        None
+    };
+
+    Ok(RenderedHtml {
+        html: sanitize(&unsafe_html),
+        highlight_css,
+        attachment_ids,
+    })
+}
+
+fn highlight(language: &str, code: &str) -> Result<String> {
+    let syntax = SYNTAXES
+        .find_syntax_by_token(language)
+        .unwrap_or_else(|| SYNTAXES.find_syntax_plain_text());
+
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, &SYNTAXES, CLASS_STYLE);
+    for line in LinesWithEndings::from(code) {
+        generator
+            .parse_html_for_line_which_includes_newline(line)
+            .map_err(|e| AppError::ValidationError(format!("Cannot highlight code: {}", e)))?;
+    }
+
+    Ok(format!(
+        "<pre class=\"hl-code\"><code>{}</code></pre>\n",
+        generator.finalize()
+    ))
+}
+
+fn rewrite_url<'a>(
+    url: CowStr<'a>,
+    options: &RenderOptions,
+    attachments: &[Attachment],
+    referenced: &mut Vec<i32>,
+) -> CowStr<'a> {
+    let name = percent_decode(url.strip_prefix(ATTACHMENT_SCHEME).unwrap_or(&url));
+    let attachment = attachments.iter().find(|attachment| {
+        attachment.file_name == name
+            || (url.starts_with(ATTACHMENT_SCHEME) && name == attachment.attachment_id.to_string())
+    });
+
+    let Some(attachment) = attachment else {
+        return url;
+    };
+
+    if !referenced.contains(&attachment.attachment_id) {
+        referenced.push(attachment.attachment_id);
+    }
+
+    let url = match options.attachment_links {
+        AttachmentLinks::Asset => asset_url(&attachment.file_path),
+        AttachmentLinks::File => file_url(&attachment.file_path),
+    };
+    CowStr::from(url)
+}
+
+// Same URL the frontend's `convertFileSrc` builds
+fn asset_url(path: &str) -> String {
+    let encoded = percent_encode(path, |b| {
+        b.is_ascii_alphanumeric() || b"-_.!~*'()".contains(&b)
+    });
+    if cfg!(any(windows, target_os = "android")) {
+        format!("http://asset.localhost/{}", encoded)
+    } else {
+        format!("asset://localhost/{}", encoded)
+    }
+}
+
+fn file_url(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let encoded = percent_encode(&path, |b| {
+        b.is_ascii_alphanumeric() || b"-_.~/:".contains(&b)
+    });
+    if encoded.starts_with('/') {
+        format!("file://{}", encoded)
+    } else {
+        format!("file:///{}", encoded)
+    }
+}
+
+fn percent_encode(text: &str, keep: impl Fn(u8) -> bool) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if keep(byte) {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{:02X}", byte);
+        }
+    }
+    encoded
+}
+
+// File names with spaces have to be written as `%20` in Markdown links
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn sanitize(html: &str) -> String {
+    ammonia::Builder::default()
+        // Task list checkboxes
+        .add_tags(&["input"])
+        .add_tag_attributes("input", &["type", "checked", "disabled"])
+        .attribute_filter(|element, attribute, value| match (element, attribute) {
+            ("input", "type") if value != "checkbox" => None,
+            _ => Some(value.into()),
+        })
+        // Highlighted code and `language-*` classes on plain code blocks
+        .add_tag_attributes("pre", &["class"])
+        .add_tag_attributes("code", &["class"])
+        .add_tag_attributes("span", &["class"])
+        // Footnote references and definitions link to each other by id
+        .add_tag_attributes("sup", &["class", "id"])
+        .add_tag_attributes("div", &["class", "id"])
+        .add_url_schemes(&["asset", "file"])
+        .clean(html)
+        .to_string()
+}
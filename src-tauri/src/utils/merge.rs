@@ -0,0 +1,265 @@
+use serde::{Deserialize, Serialize};
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+// Line-based three-way merge. Changes from both sides are combined when
+// they touch different parts of the base; where they overlap with
+// different results the region is wrapped in conflict markers:
+//
+//   <<<<<<< yours
+//   ...
+//   =======
+//   ...
+//   >>>>>>> saved
+//
+// Changes that touch adjacent lines count as overlapping, like diff3.
+
+const YOURS_MARKER: &str = "<<<<<<< yours\n";
+const SEPARATOR_MARKER: &str = "=======\n";
+const SAVED_MARKER: &str = ">>>>>>> saved\n";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeSuggestion {
+    pub content: String,
+    // Number of regions wrapped in conflict markers
+    pub conflicts: usize,
+}
+
+// A changed region: `base` lines replaced by `side` lines
+#[derive(Debug, Clone, Copy)]
+struct Hunk {
+    base_start: usize,
+    base_end: usize,
+    side_start: usize,
+    side_end: usize,
+}
+
+pub fn three_way_merge(base: &str, yours: &str, saved: &str) -> MergeSuggestion {
+    let base_lines: Vec<&str> = base.split_inclusive('\n').collect();
+    let yours_lines: Vec<&str> = yours.split_inclusive('\n').collect();
+    let saved_lines: Vec<&str> = saved.split_inclusive('\n').collect();
+
+    let yours_hunks = hunks(&base_lines, &yours_lines);
+    let saved_hunks = hunks(&base_lines, &saved_lines);
+
+    let mut merged = String::with_capacity(yours.len().max(saved.len()));
+    let mut conflicts = 0;
+    let mut position = 0;
+    let (mut y, mut s) = (0, 0);
+
+    while y < yours_hunks.len() || s < saved_hunks.len() {
+        // Start a group at whichever change comes first, then pull in every
+        // change from either side that overlaps the group as it grows
+        let mut start = usize::MAX;
+        if let Some(hunk) = yours_hunks.get(y) {
+            start = start.min(hunk.base_start);
+        }
+        if let Some(hunk) = saved_hunks.get(s) {
+            start = start.min(hunk.base_start);
+        }
+
+        let mut end = start;
+        let (group_y, group_s) = (y, s);
+        loop {
+            let before = (y, s);
+            while let Some(hunk) = yours_hunks.get(y).filter(|hunk| hunk.base_start <= end) {
+                end = end.max(hunk.base_end);
+                y += 1;
+            }
+            while let Some(hunk) = saved_hunks.get(s).filter(|hunk| hunk.base_start <= end) {
+                end = end.max(hunk.base_end);
+                s += 1;
+            }
+            if (y, s) == before {
+                break;
+            }
+        }
+
+        push_lines(&mut merged, &base_lines[position..start]);
+        position = end;
+
+        let yours_region = region(
+            &yours_hunks[group_y..y],
+            start,
+            end,
+            &yours_lines,
+            &base_lines,
+        );
+        let saved_region = region(
+            &saved_hunks[group_s..s],
+            start,
+            end,
+            &saved_lines,
+            &base_lines,
+        );
+
+        if group_y == y {
+            push_lines(&mut merged, saved_region);
+        } else if group_s == s || yours_region == saved_region {
+            push_lines(&mut merged, yours_region);
+        } else {
+            conflicts += 1;
+            merged.push_str(YOURS_MARKER);
+            push_block(&mut merged, yours_region);
+            merged.push_str(SEPARATOR_MARKER);
+            push_block(&mut merged, saved_region);
+            merged.push_str(SAVED_MARKER);
+        }
+    }
+
+    push_lines(&mut merged, &base_lines[position..]);
+
+    MergeSuggestion {
+        content: merged,
+        conflicts,
+    }
+}
+
+fn hunks(base: &[&str], side: &[&str]) -> Vec<Hunk> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+
+    for op in capture_diff_slices(Algorithm::Myers, base, side) {
+        if let DiffOp::Equal { .. } = op {
+            continue;
+        }
+        let (base_range, side_range) = (op.old_range(), op.new_range());
+
+        // A delete next to an insert is one replacement
+        match hunks.last_mut() {
+            Some(last)
+                if last.base_end == base_range.start && last.side_end == side_range.start =>
+            {
+                last.base_end = base_range.end;
+                last.side_end = side_range.end;
+            }
+            _ => hunks.push(Hunk {
+                base_start: base_range.start,
+                base_end: base_range.end,
+                side_start: side_range.start,
+                side_end: side_range.end,
+            }),
+        }
+    }
+
+    hunks
+}
+
+// The lines one side has in place of base lines `start..end`. Lines of the
+// region outside the side's own changes are unchanged from the base.
+fn region<'a>(
+    hunks: &[Hunk],
+    start: usize,
+    end: usize,
+    side: &'a [&'a str],
+    base: &'a [&'a str],
+) -> &'a [&'a str] {
+    match (hunks.first(), hunks.last()) {
+        (Some(first), Some(last)) => {
+            let side_start = first.side_start - (first.base_start - start);
+            let side_end = last.side_end + (end - last.base_end);
+            &side[side_start..side_end]
+        }
+        _ => &base[start..end],
+    }
+}
+
+fn push_lines(out: &mut String, lines: &[&str]) {
+    for line in lines {
+        out.push_str(line);
+    }
+}
+
+// Lines inside conflict markers always end with a newline, so a marker
+// never lands on the end of the last line of a block
+fn push_block(out: &mut String, lines: &[&str]) {
+    push_lines(out, lines);
+    if !lines.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge(base: &str, yours: &str, saved: &str) -> (String, usize) {
+        let suggestion = three_way_merge(base, yours, saved);
+        (suggestion.content, suggestion.conflicts)
+    }
+
+    #[test]
+    fn one_sided_changes() {
+        let base = "a\nb\nc\n";
+        assert_eq!(merge(base, base, base), (base.to_string(), 0));
+        assert_eq!(merge(base, "a\nB\nc\n", base), ("a\nB\nc\n".to_string(), 0));
+        assert_eq!(
+            merge(base, base, "a\nb\nc\nd\n"),
+            ("a\nb\nc\nd\n".to_string(), 0)
+        );
+    }
+
+    #[test]
+    fn separate_changes_combine() {
+        let base = "1\n2\n3\n4\n5\n";
+        assert_eq!(
+            merge(base, "one\n2\n3\n4\n5\n", "1\n2\n3\n4\nfive\n"),
+            ("one\n2\n3\n4\nfive\n".to_string(), 0)
+        );
+        // A deletion on one side and an insertion elsewhere on the other
+        assert_eq!(
+            merge(base, "1\n3\n4\n5\n", "1\n2\n3\n4\n4.5\n5\n"),
+            ("1\n3\n4\n4.5\n5\n".to_string(), 0)
+        );
+    }
+
+    #[test]
+    fn identical_changes_are_not_conflicts() {
+        let base = "a\nb\nc\n";
+        assert_eq!(
+            merge(base, "a\nX\nc\n", "a\nX\nc\n"),
+            ("a\nX\nc\n".to_string(), 0)
+        );
+    }
+
+    #[test]
+    fn overlapping_changes_conflict() {
+        let base = "a\nb\nc\n";
+        assert_eq!(
+            merge(base, "a\nmine\nc\n", "a\ntheirs\nc\n"),
+            (
+                "a\n<<<<<<< yours\nmine\n=======\ntheirs\n>>>>>>> saved\nc\n".to_string(),
+                1
+            )
+        );
+    }
+
+    #[test]
+    fn adjacent_changes_conflict() {
+        let base = "a\nb\nc\nd\n";
+        let (content, conflicts) = merge(base, "a\nB\nc\nd\n", "a\nb\nC\nd\n");
+        assert_eq!(conflicts, 1);
+        assert_eq!(
+            content,
+            "a\n<<<<<<< yours\nB\nc\n=======\nb\nC\n>>>>>>> saved\nd\n"
+        );
+    }
+
+    #[test]
+    fn markers_start_on_their_own_line() {
+        // Neither side ends with a newline
+        let (content, conflicts) = merge("x", "yours", "saved");
+        assert_eq!(conflicts, 1);
+        assert_eq!(
+            content,
+            "<<<<<<< yours\nyours\n=======\nsaved\n>>>>>>> saved\n"
+        );
+    }
+
+    #[test]
+    fn multiple_conflicts_are_counted() {
+        let base = "1\n2\n3\n4\n5\n";
+        let (content, conflicts) = merge(base, "A\n2\n3\n4\nE\n", "a\n2\n3\n4\ne\n");
+        assert_eq!(conflicts, 2);
+        assert_eq!(content.matches(YOURS_MARKER).count(), 2);
+        assert!(content.contains("\n2\n3\n4\n"));
+    }
+}
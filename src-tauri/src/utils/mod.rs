@@ -1,10 +1,46 @@
+pub mod diff;
 pub mod error;
+pub mod folder_path;
+pub mod frontmatter;
 pub mod helpers;
+pub mod markdown;
+pub mod merge;
+pub mod property_query;
+pub mod reminder_time;
+pub mod search_query;
+pub mod similarity;
+pub mod tasks;
+pub mod template;
 pub mod validation;
+pub mod wiki_links;
 
+#[allow(unused_imports)]
+pub use diff::*;
 #[allow(unused_imports)]
 pub use error::*;
 #[allow(unused_imports)]
+pub use folder_path::*;
+#[allow(unused_imports)]
+pub use frontmatter::*;
+#[allow(unused_imports)]
 pub use helpers::*;
 #[allow(unused_imports)]
+pub use markdown::*;
+#[allow(unused_imports)]
+pub use merge::*;
+#[allow(unused_imports)]
+pub use property_query::*;
+#[allow(unused_imports)]
+pub use reminder_time::*;
+#[allow(unused_imports)]
+pub use search_query::*;
+#[allow(unused_imports)]
+pub use similarity::*;
+#[allow(unused_imports)]
+pub use tasks::*;
+#[allow(unused_imports)]
+pub use template::*;
+#[allow(unused_imports)]
 pub use validation::*;
+#[allow(unused_imports)]
+pub use wiki_links::*;
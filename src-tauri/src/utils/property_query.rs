@@ -0,0 +1,420 @@
+use crate::utils::error::{AppError, Result};
+use chrono::NaiveDate;
+
+// Parser for note property filters, e.g.
+//
+//   status = draft AND priority >= 2 AND NOT archived_on
+//
+// A comparison is a key, an operator (`=`, `!=`, `<`, `<=`, `>`, `>=`) and
+// a value; a key on its own matches notes that have the property. `AND`
+// (or plain whitespace), `OR` and `NOT` combine conditions, `AND` binds
+// tighter than `OR` and parentheses group. Keywords and keys are
+// case-insensitive. Bare values are typed: `true`/`false` are booleans,
+// `YYYY-MM-DD` is a date and numbers are numbers; anything else, and every
+// double-quoted value, is text. Positions reported in errors are character
+// offsets into the filter, starting at 0.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyExpr {
+    And(Vec<PropertyExpr>),
+    Or(Vec<PropertyExpr>),
+    Not(Box<PropertyExpr>),
+    Exists(String),
+    Compare {
+        key: String,
+        op: CompareOp,
+        value: PropertyLiteral,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    pub fn sql(&self) -> &'static str {
+        match self {
+            CompareOp::Eq => "=",
+            CompareOp::Ne => "<>",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyLiteral {
+    Text(String),
+    Number(f64),
+    Date(NaiveDate),
+    Boolean(bool),
+}
+
+impl PropertyExpr {
+    pub fn parse(input: &str) -> Result<Self> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            return Err(AppError::ValidationError(
+                "Property filter cannot be empty".to_string(),
+            ));
+        }
+
+        let mut parser = Parser {
+            tokens,
+            index: 0,
+            end: input.chars().count(),
+        };
+        let expr = parser.parse_or()?;
+
+        if let Some(token) = parser.peek() {
+            return Err(error_at("Unexpected ')'", token.position));
+        }
+
+        Ok(expr)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Quoted(String),
+    Op(CompareOp),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let position = i;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let followed_by_equals = chars.get(i + 1) == Some(&'=');
+        let kind = match c {
+            '(' => {
+                i += 1;
+                TokenKind::Open
+            }
+            ')' => {
+                i += 1;
+                TokenKind::Close
+            }
+            '=' => {
+                i += 1;
+                TokenKind::Op(CompareOp::Eq)
+            }
+            '!' if followed_by_equals => {
+                i += 2;
+                TokenKind::Op(CompareOp::Ne)
+            }
+            '<' | '>' => {
+                i += if followed_by_equals { 2 } else { 1 };
+                TokenKind::Op(match (c, followed_by_equals) {
+                    ('<', false) => CompareOp::Lt,
+                    ('<', true) => CompareOp::Le,
+                    ('>', false) => CompareOp::Gt,
+                    _ => CompareOp::Ge,
+                })
+            }
+            '"' => match chars[i + 1..].iter().position(|&c| c == '"') {
+                Some(length) => {
+                    let text = chars[i + 1..i + 1 + length].iter().collect();
+                    i += length + 2;
+                    TokenKind::Quoted(text)
+                }
+                None => return Err(error_at("Unterminated quote", position)),
+            },
+            _ => {
+                while i < chars.len() && !is_delimiter(chars[i]) {
+                    i += 1;
+                }
+                if i == position {
+                    return Err(error_at(&format!("Unexpected '{}'", c), position));
+                }
+                let word: String = chars[position..i].iter().collect();
+                match word.to_uppercase().as_str() {
+                    "AND" => TokenKind::And,
+                    "OR" => TokenKind::Or,
+                    "NOT" => TokenKind::Not,
+                    _ => TokenKind::Word(word),
+                }
+            }
+        };
+
+        tokens.push(Token { kind, position });
+    }
+
+    Ok(tokens)
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '(' | ')' | '"' | '=' | '!' | '<' | '>')
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.index).cloned();
+        self.index += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<PropertyExpr> {
+        let mut branches = vec![self.parse_and()?];
+
+        while self.peek().is_some_and(|t| t.kind == TokenKind::Or) {
+            self.next();
+            branches.push(self.parse_and()?);
+        }
+
+        Ok(if branches.len() == 1 {
+            branches.remove(0)
+        } else {
+            PropertyExpr::Or(branches)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<PropertyExpr> {
+        let mut items = vec![self.parse_unary()?];
+
+        loop {
+            match self.peek().map(|t| &t.kind) {
+                Some(TokenKind::And) => {
+                    self.next();
+                    items.push(self.parse_unary()?);
+                }
+                None | Some(TokenKind::Or | TokenKind::Close) => break,
+                Some(_) => items.push(self.parse_unary()?),
+            }
+        }
+
+        Ok(if items.len() == 1 {
+            items.remove(0)
+        } else {
+            PropertyExpr::And(items)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<PropertyExpr> {
+        if self.peek().is_some_and(|t| t.kind == TokenKind::Not) {
+            self.next();
+            return Ok(PropertyExpr::Not(Box::new(self.parse_unary()?)));
+        }
+
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<PropertyExpr> {
+        let Some(token) = self.next() else {
+            return Err(error_at("Expected a property", self.end));
+        };
+
+        let key = match token.kind {
+            TokenKind::Open => {
+                let inner = self.parse_or()?;
+                return match self.next() {
+                    Some(Token {
+                        kind: TokenKind::Close,
+                        ..
+                    }) => Ok(inner),
+                    _ => Err(error_at("Missing ')' for the group opened", token.position)),
+                };
+            }
+            TokenKind::Word(key) | TokenKind::Quoted(key) if !key.trim().is_empty() => {
+                key.trim().to_string()
+            }
+            _ => return Err(error_at("Expected a property", token.position)),
+        };
+
+        let Some(TokenKind::Op(op)) = self.peek().map(|t| t.kind.clone()) else {
+            return Ok(PropertyExpr::Exists(key));
+        };
+        self.next();
+
+        let value = match self.next() {
+            Some(Token {
+                kind: TokenKind::Quoted(text),
+                ..
+            }) => PropertyLiteral::Text(text),
+            Some(Token {
+                kind: TokenKind::Word(word),
+                ..
+            }) => literal(word),
+            Some(token) => {
+                return Err(error_at(
+                    &format!("Expected a value for '{}'", key),
+                    token.position,
+                ))
+            }
+            None => {
+                return Err(error_at(
+                    &format!("Expected a value for '{}'", key),
+                    self.end,
+                ))
+            }
+        };
+
+        Ok(PropertyExpr::Compare { key, op, value })
+    }
+}
+
+fn literal(word: String) -> PropertyLiteral {
+    match word.to_lowercase().as_str() {
+        "true" => return PropertyLiteral::Boolean(true),
+        "false" => return PropertyLiteral::Boolean(false),
+        _ => {}
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&word, "%Y-%m-%d") {
+        return PropertyLiteral::Date(date);
+    }
+    match word.parse::<f64>() {
+        Ok(number) if number.is_finite() => PropertyLiteral::Number(number),
+        _ => PropertyLiteral::Text(word),
+    }
+}
+
+fn error_at(message: &str, position: usize) -> AppError {
+    AppError::ValidationError(format!("{} at position {}", message, position))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compare(key: &str, op: CompareOp, value: PropertyLiteral) -> PropertyExpr {
+        PropertyExpr::Compare {
+            key: key.to_string(),
+            op,
+            value,
+        }
+    }
+
+    fn exists(key: &str) -> PropertyExpr {
+        PropertyExpr::Exists(key.to_string())
+    }
+
+    fn text(value: &str) -> PropertyLiteral {
+        PropertyLiteral::Text(value.to_string())
+    }
+
+    fn error(input: &str) -> String {
+        match PropertyExpr::parse(input) {
+            Err(AppError::ValidationError(message)) => message,
+            other => panic!("{input:?} should be rejected, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn operators_and_literals() {
+        let date = NaiveDate::from_ymd_opt(2026, 11, 1).unwrap();
+        let cases = [
+            (
+                "status = draft",
+                compare("status", CompareOp::Eq, text("draft")),
+            ),
+            (
+                "status!=\"a b\"",
+                compare("status", CompareOp::Ne, text("a b")),
+            ),
+            (
+                "priority < 2",
+                compare("priority", CompareOp::Lt, PropertyLiteral::Number(2.0)),
+            ),
+            (
+                "priority<=-1.5",
+                compare("priority", CompareOp::Le, PropertyLiteral::Number(-1.5)),
+            ),
+            (
+                "due > 2026-11-01",
+                compare("due", CompareOp::Gt, PropertyLiteral::Date(date)),
+            ),
+            (
+                "done >= TRUE",
+                compare("done", CompareOp::Ge, PropertyLiteral::Boolean(true)),
+            ),
+            ("n = \"2\"", compare("n", CompareOp::Eq, text("2"))),
+            ("n = inf", compare("n", CompareOp::Eq, text("inf"))),
+            (
+                "d = 2026-02-30",
+                compare("d", CompareOp::Eq, text("2026-02-30")),
+            ),
+            (
+                "\"due date\" = x",
+                compare("due date", CompareOp::Eq, text("x")),
+            ),
+            ("archived_on", exists("archived_on")),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(PropertyExpr::parse(input).unwrap(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            PropertyExpr::parse("a and b c OR NOT (d Or e)").unwrap(),
+            PropertyExpr::Or(vec![
+                PropertyExpr::And(vec![exists("a"), exists("b"), exists("c")]),
+                PropertyExpr::Not(Box::new(PropertyExpr::Or(vec![exists("d"), exists("e")]))),
+            ])
+        );
+        assert_eq!(
+            PropertyExpr::parse("not not a").unwrap(),
+            PropertyExpr::Not(Box::new(PropertyExpr::Not(Box::new(exists("a")))))
+        );
+    }
+
+    #[test]
+    fn error_positions() {
+        assert_eq!(error(" "), "Property filter cannot be empty");
+        assert_eq!(error("a = \"x"), "Unterminated quote at position 4");
+        assert_eq!(error("a ! b"), "Unexpected '!' at position 2");
+        assert_eq!(error("a ="), "Expected a value for 'a' at position 3");
+        assert_eq!(error("a = )"), "Expected a value for 'a' at position 4");
+        assert_eq!(error("a = = b"), "Expected a value for 'a' at position 4");
+        assert_eq!(error("= b"), "Expected a property at position 0");
+        assert_eq!(error("a AND"), "Expected a property at position 5");
+        assert_eq!(error("OR a"), "Expected a property at position 0");
+        assert_eq!(error("\" \" = 1"), "Expected a property at position 0");
+        assert_eq!(
+            error("(a OR b"),
+            "Missing ')' for the group opened at position 0"
+        );
+        assert_eq!(error("a)"), "Unexpected ')' at position 1");
+        assert_eq!(error("é = \"x"), "Unterminated quote at position 4");
+    }
+}
@@ -0,0 +1,381 @@
+use crate::utils::error::{AppError, Result};
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday,
+};
+
+// Reminder times as typed by the user. Accepted forms, case-insensitive:
+//
+//   in 2 hours, in 30 min, in an hour, in 1 day 2 hours, in 90m
+//   tomorrow 9am, today at 17:30, tonight, friday noon, next monday morning
+//   9:30pm (today, or tomorrow once that time has passed)
+//   2026-03-10, 2026-03-10 14:00, RFC 3339 timestamps
+//
+// A day without a time means 9am, or 8pm for `tonight`. The result must lie
+// in the future.
+
+const DEFAULT_TIME: (u32, u32) = (9, 0);
+const TONIGHT_TIME: (u32, u32) = (20, 0);
+
+pub fn parse_reminder_time(input: &str, now: DateTime<Local>) -> Result<DateTime<Local>> {
+    let text = input.trim();
+    if text.is_empty() {
+        return Err(AppError::ValidationError(
+            "Reminder time cannot be empty".to_string(),
+        ));
+    }
+
+    let time = match parse_absolute(text) {
+        Some(time) => time,
+        None => {
+            let lowered = text.to_lowercase().replace(',', " ");
+            let tokens: Vec<&str> = lowered.split_whitespace().collect();
+            match tokens.first() {
+                None => return Err(not_understood(text)),
+                Some(&"in") => parse_relative(&tokens[1..], text, now)?,
+                Some(_) => parse_day_and_time(&tokens, text, now)?,
+            }
+        }
+    };
+
+    if time <= now {
+        return Err(AppError::ValidationError(format!(
+            "Reminder time '{}' is in the past",
+            text
+        )));
+    }
+
+    Ok(time)
+}
+
+// Resolve a wall-clock time in the local zone. Times skipped by a DST jump
+// move forward by the size of the gap.
+pub fn to_local(naive: NaiveDateTime) -> DateTime<Local> {
+    match Local.from_local_datetime(&naive).earliest() {
+        Some(time) => time,
+        None => Local
+            .from_local_datetime(&(naive + Duration::hours(1)))
+            .earliest()
+            .unwrap_or_else(|| Local.from_utc_datetime(&naive)),
+    }
+}
+
+fn parse_absolute(text: &str) -> Option<DateTime<Local>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Some(time.with_timezone(&Local));
+    }
+
+    for format in ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(text, format) {
+            return Some(to_local(naive));
+        }
+    }
+
+    None
+}
+
+// `2 hours 30 minutes`, `an hour`, `90m`, ...
+fn parse_relative(tokens: &[&str], text: &str, now: DateTime<Local>) -> Result<DateTime<Local>> {
+    if tokens.is_empty() {
+        return Err(not_understood(text));
+    }
+
+    let mut total = Duration::zero();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = tokens[i];
+        if token == "and" {
+            i += 1;
+            continue;
+        }
+
+        // `90m` and `2h` carry their unit; otherwise the unit is the next word
+        let split = token
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(token.len());
+        let (amount, unit) = if split > 0 && split < token.len() {
+            (&token[..split], &token[split..])
+        } else {
+            i += 1;
+            (token, *tokens.get(i).ok_or_else(|| not_understood(text))?)
+        };
+
+        let amount: i32 = match amount {
+            "a" | "an" => 1,
+            _ => amount.parse().map_err(|_| not_understood(text))?,
+        };
+
+        let unit = match unit {
+            "m" | "min" | "mins" | "minute" | "minutes" => Duration::minutes(1),
+            "h" | "hr" | "hrs" | "hour" | "hours" => Duration::hours(1),
+            "d" | "day" | "days" => Duration::days(1),
+            "w" | "week" | "weeks" => Duration::weeks(1),
+            _ => return Err(not_understood(text)),
+        };
+
+        total = unit
+            .checked_mul(amount)
+            .and_then(|step| total.checked_add(&step))
+            .ok_or_else(|| not_understood(text))?;
+        i += 1;
+    }
+
+    now.checked_add_signed(total)
+        .ok_or_else(|| not_understood(text))
+}
+
+// A day (`today`, `tomorrow`, a weekday or a date) and/or a time of day, in
+// either order
+fn parse_day_and_time(
+    tokens: &[&str],
+    text: &str,
+    now: DateTime<Local>,
+) -> Result<DateTime<Local>> {
+    let today = now.date_naive();
+    let mut day: Option<NaiveDate> = None;
+    let mut time: Option<NaiveTime> = None;
+    let mut default_time = DEFAULT_TIME;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = tokens[i];
+        i += 1;
+
+        let parsed_day = match token {
+            "at" | "next" | "on" => continue,
+            "today" => Some(today),
+            "tonight" => {
+                default_time = TONIGHT_TIME;
+                Some(today)
+            }
+            "tomorrow" => today.succ_opt(),
+            _ => match parse_weekday(token) {
+                Some(weekday) => Some(next_weekday(today, weekday)),
+                None => NaiveDate::parse_from_str(token, "%Y-%m-%d").ok(),
+            },
+        };
+
+        if let Some(parsed_day) = parsed_day {
+            if day.replace(parsed_day).is_some() {
+                return Err(not_understood(text));
+            }
+            continue;
+        }
+
+        let parsed_time = match token {
+            "morning" => NaiveTime::from_hms_opt(9, 0, 0),
+            "noon" | "midday" => NaiveTime::from_hms_opt(12, 0, 0),
+            "afternoon" => NaiveTime::from_hms_opt(14, 0, 0),
+            "evening" => NaiveTime::from_hms_opt(18, 0, 0),
+            "midnight" => NaiveTime::from_hms_opt(0, 0, 0),
+            _ => {
+                // `9 am` is split across two tokens
+                let meridiem = tokens.get(i).filter(|next| matches!(**next, "am" | "pm"));
+                match meridiem {
+                    Some(meridiem) => {
+                        i += 1;
+                        parse_clock(&format!("{}{}", token, meridiem))
+                    }
+                    None => parse_clock(token),
+                }
+            }
+        };
+
+        match parsed_time {
+            Some(parsed_time) if time.is_none() => time = Some(parsed_time),
+            _ => return Err(not_understood(text)),
+        }
+    }
+
+    let (hour, minute) = default_time;
+    let at = match (day, time) {
+        (Some(day), Some(time)) => day.and_time(time),
+        (Some(day), None) => day.and_hms_opt(hour, minute, 0).unwrap(),
+        // A bare time means its next occurrence
+        (None, Some(time)) => {
+            let at = today.and_time(time);
+            if to_local(at) > now {
+                at
+            } else {
+                at + Duration::days(1)
+            }
+        }
+        (None, None) => return Err(not_understood(text)),
+    };
+
+    Ok(to_local(at))
+}
+
+// `9am`, `9:30pm`, `12am`, `17:00`
+fn parse_clock(token: &str) -> Option<NaiveTime> {
+    let (clock, meridiem) = if let Some(clock) = token.strip_suffix("am") {
+        (clock, Some(false))
+    } else if let Some(clock) = token.strip_suffix("pm") {
+        (clock, Some(true))
+    } else {
+        (token, None)
+    };
+
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => {
+            (hour.parse::<u32>().ok()?, minute.parse().ok()?)
+        }
+        Some(_) => return None,
+        // A bare number is only a time with am/pm after it
+        None if meridiem.is_some() => (clock.parse::<u32>().ok()?, 0),
+        None => return None,
+    };
+
+    let hour = match meridiem {
+        Some(pm) if (1..=12).contains(&hour) => hour % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        None => hour,
+    };
+
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+fn parse_weekday(token: &str) -> Option<Weekday> {
+    let weekday = match token {
+        "mon" | "monday" => Weekday::Mon,
+        "tue" | "tues" | "tuesday" => Weekday::Tue,
+        "wed" | "wednesday" => Weekday::Wed,
+        "thu" | "thur" | "thurs" | "thursday" => Weekday::Thu,
+        "fri" | "friday" => Weekday::Fri,
+        "sat" | "saturday" => Weekday::Sat,
+        "sun" | "sunday" => Weekday::Sun,
+        _ => return None,
+    };
+    Some(weekday)
+}
+
+// The next `weekday` strictly after `today`
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    let ahead = if ahead == 0 { 7 } else { ahead };
+    today + Duration::days(ahead.into())
+}
+
+fn not_understood(text: &str) -> AppError {
+    AppError::ValidationError(format!(
+        "Could not understand reminder time '{}' (try \"tomorrow 9am\" or \"in 2 hours\")",
+        text
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Wednesday 2026-06-10, 10:00 local time
+    fn now() -> DateTime<Local> {
+        to_local(
+            NaiveDate::from_ymd_opt(2026, 6, 10)
+                .unwrap()
+                .and_hms_opt(10, 0, 0)
+                .unwrap(),
+        )
+    }
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Local> {
+        to_local(
+            NaiveDate::from_ymd_opt(y, m, d)
+                .unwrap()
+                .and_hms_opt(h, min, 0)
+                .unwrap(),
+        )
+    }
+
+    fn parse(input: &str) -> Result<DateTime<Local>> {
+        parse_reminder_time(input, now())
+    }
+
+    #[test]
+    fn relative_times() {
+        assert_eq!(parse("in 2 hours").unwrap(), now() + Duration::hours(2));
+        assert_eq!(parse("in 30 min").unwrap(), now() + Duration::minutes(30));
+        assert_eq!(parse("In an hour").unwrap(), now() + Duration::hours(1));
+        assert_eq!(parse("in 90m").unwrap(), now() + Duration::minutes(90));
+        assert_eq!(
+            parse("in 1 day and 2 hours").unwrap(),
+            now() + Duration::days(1) + Duration::hours(2)
+        );
+        assert_eq!(parse("in 2w").unwrap(), now() + Duration::weeks(2));
+    }
+
+    #[test]
+    fn days_and_times() {
+        assert_eq!(parse("tomorrow 9am").unwrap(), at(2026, 6, 11, 9, 0));
+        assert_eq!(parse("9 am tomorrow").unwrap(), at(2026, 6, 11, 9, 0));
+        assert_eq!(parse("today at 17:30").unwrap(), at(2026, 6, 10, 17, 30));
+        assert_eq!(parse("tonight").unwrap(), at(2026, 6, 10, 20, 0));
+        assert_eq!(parse("Friday noon").unwrap(), at(2026, 6, 12, 12, 0));
+        assert_eq!(parse("next monday morning").unwrap(), at(2026, 6, 15, 9, 0));
+        // The same weekday means a week from today
+        assert_eq!(parse("wednesday").unwrap(), at(2026, 6, 17, 9, 0));
+        assert_eq!(parse("tomorrow, 12am").unwrap(), at(2026, 6, 11, 0, 0));
+    }
+
+    #[test]
+    fn bare_times_roll_over_to_tomorrow() {
+        assert_eq!(parse("9:30pm").unwrap(), at(2026, 6, 10, 21, 30));
+        assert_eq!(parse("8am").unwrap(), at(2026, 6, 11, 8, 0));
+        assert_eq!(parse("10:00").unwrap(), at(2026, 6, 11, 10, 0));
+    }
+
+    #[test]
+    fn absolute_times() {
+        assert_eq!(parse("2026-07-01").unwrap(), at(2026, 7, 1, 9, 0));
+        assert_eq!(parse("2026-07-01 14:00").unwrap(), at(2026, 7, 1, 14, 0));
+        assert_eq!(parse("2026-07-01T14:00").unwrap(), at(2026, 7, 1, 14, 0));
+        assert_eq!(
+            parse("2026-07-01T12:00:00Z").unwrap(),
+            DateTime::parse_from_rfc3339("2026-07-01T12:00:00Z")
+                .unwrap()
+                .with_timezone(&Local)
+        );
+    }
+
+    #[test]
+    fn rejects_past_times() {
+        assert!(parse("today 9am").is_err());
+        assert!(parse("2026-06-01").is_err());
+        assert!(parse("in 0 minutes").is_err());
+    }
+
+    #[test]
+    fn rejects_text_it_cannot_read() {
+        for input in [
+            "",
+            "   ",
+            ",",
+            ", ,",
+            "in",
+            "in 2",
+            "in two hours",
+            "in 2 fortnights",
+            "tomorrow tomorrow",
+            "9am 10am",
+            "13pm",
+            "0am",
+            "9:5",
+            "someday",
+            "2026-02-30",
+        ] {
+            assert!(
+                matches!(parse(input), Err(AppError::ValidationError(_))),
+                "{input:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn clock_formats() {
+        assert_eq!(parse_clock("12am"), NaiveTime::from_hms_opt(0, 0, 0));
+        assert_eq!(parse_clock("12pm"), NaiveTime::from_hms_opt(12, 0, 0));
+        assert_eq!(parse_clock("9:30pm"), NaiveTime::from_hms_opt(21, 30, 0));
+        assert_eq!(parse_clock("17:00"), NaiveTime::from_hms_opt(17, 0, 0));
+        assert_eq!(parse_clock("17"), None);
+        assert_eq!(parse_clock("25:00"), None);
+    }
+}
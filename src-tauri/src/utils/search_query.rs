@@ -0,0 +1,566 @@
+use crate::utils::error::{AppError, Result};
+use crate::utils::folder_path::parse_folder_path;
+use chrono::NaiveDate;
+
+// Parser for the search box syntax, e.g.
+//
+//   tag:work folder:"Projects/Q3" is:pinned -is:archived after:2026-01-01 meeting notes
+//
+// Terms separated by whitespace must all match, `OR` between terms matches
+// either side and binds looser than the implicit AND, parentheses group
+// terms and a leading `-` negates a term or group. Positions reported in
+// errors are character offsets into the query, starting at 0.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchExpr {
+    And(Vec<SearchExpr>),
+    Or(Vec<SearchExpr>),
+    Not(Box<SearchExpr>),
+    Term(SearchTerm),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchTerm {
+    // Full-text matches against title and content
+    Word(String),
+    Phrase(String),
+    // Tag name, case-insensitive
+    Tag(String),
+    // Folder path split into names as by `parse_folder_path`, matched
+    // case-insensitively against the end of a folder's path; subfolders are included
+    Folder(Vec<String>),
+    Is(NoteFlag),
+    // Creation date bounds: `before:` excludes the given day, `after:` includes it
+    Before(NaiveDate),
+    After(NaiveDate),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteFlag {
+    Pinned,
+    Archived,
+}
+
+impl SearchExpr {
+    pub fn parse(input: &str) -> Result<Self> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            return Err(AppError::ValidationError(
+                "Search query cannot be empty".to_string(),
+            ));
+        }
+
+        let mut parser = Parser {
+            tokens,
+            index: 0,
+            end: input.chars().count(),
+        };
+        let expr = parser.parse_or()?;
+
+        // parse_or only stops early on a closing parenthesis without a matching group
+        if let Some(token) = parser.peek() {
+            return Err(error_at("Unexpected ')'", token.position));
+        }
+
+        Ok(expr)
+    }
+
+    // Positive text terms as a `websearch_to_tsquery` string, used to rank
+    // hits and highlight snippets. `None` when the query only has filters.
+    pub fn rank_text(&self) -> Option<String> {
+        let terms: Vec<String> = self
+            .text_terms()
+            .into_iter()
+            .map(|term| match term {
+                SearchTerm::Phrase(phrase) => format!("\"{}\"", phrase),
+                SearchTerm::Word(word) => word.clone(),
+                _ => String::new(),
+            })
+            .collect();
+
+        if terms.is_empty() {
+            None
+        } else {
+            Some(terms.join(" or "))
+        }
+    }
+
+    // Positive text terms as plain words, used to rank fuzzy matches
+    pub fn fuzzy_text(&self) -> Option<String> {
+        let terms: Vec<&str> = self
+            .text_terms()
+            .into_iter()
+            .map(|term| match term {
+                SearchTerm::Word(text) | SearchTerm::Phrase(text) => text.as_str(),
+                _ => "",
+            })
+            .collect();
+
+        if terms.is_empty() {
+            None
+        } else {
+            Some(terms.join(" "))
+        }
+    }
+
+    // Words and phrases that are not negated
+    fn text_terms(&self) -> Vec<&SearchTerm> {
+        match self {
+            SearchExpr::And(items) | SearchExpr::Or(items) => {
+                items.iter().flat_map(|item| item.text_terms()).collect()
+            }
+            SearchExpr::Not(_) => Vec::new(),
+            SearchExpr::Term(term @ (SearchTerm::Word(_) | SearchTerm::Phrase(_))) => vec![term],
+            SearchExpr::Term(_) => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Phrase(String),
+    Field {
+        name: String,
+        value: String,
+        value_position: usize,
+    },
+    Not,
+    Or,
+    Open,
+    Close,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+const FIELDS: &[&str] = &["tag", "folder", "is", "before", "after"];
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let position = i;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let kind = match c {
+            '(' => {
+                i += 1;
+                TokenKind::Open
+            }
+            ')' => {
+                i += 1;
+                TokenKind::Close
+            }
+            // A dash only negates when it is attached to the following term
+            '-' if chars.get(i + 1).is_some_and(|next| !next.is_whitespace()) => {
+                i += 1;
+                TokenKind::Not
+            }
+            '"' => {
+                let (phrase, next) = read_quoted(&chars, i)?;
+                i = next;
+                if phrase.trim().is_empty() {
+                    return Err(error_at("Empty phrase", position));
+                }
+                TokenKind::Phrase(phrase)
+            }
+            _ => {
+                while i < chars.len() && !is_delimiter(chars[i]) {
+                    i += 1;
+                }
+                let word: String = chars[position..i].iter().collect();
+
+                match word.split_once(':') {
+                    Some((name, value)) if FIELDS.contains(&name.to_lowercase().as_str()) => {
+                        let name = name.to_lowercase();
+                        let value_position = position + name.chars().count() + 1;
+
+                        let value = if !value.is_empty() {
+                            value.to_string()
+                        } else if chars.get(i) == Some(&'"') {
+                            let (value, next) = read_quoted(&chars, i)?;
+                            i = next;
+                            value
+                        } else {
+                            String::new()
+                        };
+
+                        if value.trim().is_empty() {
+                            return Err(error_at(
+                                &format!("Expected a value after '{}:'", name),
+                                value_position,
+                            ));
+                        }
+
+                        TokenKind::Field {
+                            name,
+                            value,
+                            value_position,
+                        }
+                    }
+                    _ if word == "OR" => TokenKind::Or,
+                    _ => TokenKind::Word(word),
+                }
+            }
+        };
+
+        tokens.push(Token { kind, position });
+    }
+
+    Ok(tokens)
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || c == '(' || c == ')' || c == '"'
+}
+
+// Read a double-quoted string starting at `start`, returning its contents
+// and the index just past the closing quote
+fn read_quoted(chars: &[char], start: usize) -> Result<(String, usize)> {
+    match chars[start + 1..].iter().position(|&c| c == '"') {
+        Some(length) => {
+            let end = start + 1 + length;
+            Ok((chars[start + 1..end].iter().collect(), end + 1))
+        }
+        None => Err(error_at("Unterminated quote", start)),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.index).cloned();
+        self.index += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<SearchExpr> {
+        let mut branches = vec![self.parse_and()?];
+
+        while self.peek().is_some_and(|t| t.kind == TokenKind::Or) {
+            self.next();
+            branches.push(self.parse_and()?);
+        }
+
+        Ok(if branches.len() == 1 {
+            branches.remove(0)
+        } else {
+            SearchExpr::Or(branches)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<SearchExpr> {
+        let mut items = Vec::new();
+
+        while let Some(token) = self.peek() {
+            if matches!(token.kind, TokenKind::Or | TokenKind::Close) {
+                break;
+            }
+            items.push(self.parse_unary()?);
+        }
+
+        match items.len() {
+            0 => Err(self.expected_term()),
+            1 => Ok(items.remove(0)),
+            _ => Ok(SearchExpr::And(items)),
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<SearchExpr> {
+        if self.peek().is_some_and(|t| t.kind == TokenKind::Not) {
+            self.next();
+            if !self
+                .peek()
+                .is_some_and(|t| !matches!(t.kind, TokenKind::Or | TokenKind::Close))
+            {
+                return Err(self.expected_term());
+            }
+            return Ok(SearchExpr::Not(Box::new(self.parse_unary()?)));
+        }
+
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<SearchExpr> {
+        let Some(token) = self.next() else {
+            return Err(error_at("Expected a search term", self.end));
+        };
+
+        match token.kind {
+            TokenKind::Open => {
+                let inner = self.parse_or()?;
+                match self.next() {
+                    Some(Token {
+                        kind: TokenKind::Close,
+                        ..
+                    }) => Ok(inner),
+                    _ => Err(error_at("Missing ')' for the group opened", token.position)),
+                }
+            }
+            TokenKind::Word(word) => Ok(SearchExpr::Term(SearchTerm::Word(word))),
+            TokenKind::Phrase(phrase) => Ok(SearchExpr::Term(SearchTerm::Phrase(phrase))),
+            TokenKind::Field {
+                name,
+                value,
+                value_position,
+            } => Ok(SearchExpr::Term(parse_field(&name, value, value_position)?)),
+            TokenKind::Not | TokenKind::Or | TokenKind::Close => {
+                Err(error_at("Expected a search term", token.position))
+            }
+        }
+    }
+
+    fn expected_term(&self) -> AppError {
+        match self.peek() {
+            Some(token) if token.kind == TokenKind::Or => {
+                error_at("Expected a search term before 'OR'", token.position)
+            }
+            Some(token) => error_at("Expected a search term before ')'", token.position),
+            None => error_at("Expected a search term", self.end),
+        }
+    }
+}
+
+fn parse_field(name: &str, value: String, position: usize) -> Result<SearchTerm> {
+    match name {
+        "tag" => Ok(SearchTerm::Tag(value)),
+        "folder" => parse_folder_path(&value)
+            .map(SearchTerm::Folder)
+            .map_err(|e| match e {
+                AppError::ValidationError(message) => error_at(&message, position),
+                e => e,
+            }),
+        "is" => match value.to_lowercase().as_str() {
+            "pinned" => Ok(SearchTerm::Is(NoteFlag::Pinned)),
+            "archived" => Ok(SearchTerm::Is(NoteFlag::Archived)),
+            _ => Err(error_at(
+                &format!(
+                    "Unknown value '{}' for 'is:' (expected pinned or archived)",
+                    value
+                ),
+                position,
+            )),
+        },
+        "before" | "after" => {
+            let date = NaiveDate::parse_from_str(&value, "%Y-%m-%d").map_err(|_| {
+                error_at(
+                    &format!("Invalid date '{}' (expected YYYY-MM-DD)", value),
+                    position,
+                )
+            })?;
+            Ok(if name == "before" {
+                SearchTerm::Before(date)
+            } else {
+                SearchTerm::After(date)
+            })
+        }
+        _ => Err(error_at(&format!("Unknown filter '{}:'", name), position)),
+    }
+}
+
+fn error_at(message: &str, position: usize) -> AppError {
+    AppError::ValidationError(format!("{} at position {}", message, position))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(term: SearchTerm) -> SearchExpr {
+        SearchExpr::Term(term)
+    }
+
+    fn word(text: &str) -> SearchExpr {
+        term(SearchTerm::Word(text.to_string()))
+    }
+
+    fn error(input: &str) -> String {
+        match SearchExpr::parse(input) {
+            Err(AppError::ValidationError(message)) => message,
+            other => panic!("{input:?} should be rejected, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn words_are_anded() {
+        assert_eq!(SearchExpr::parse("meeting").unwrap(), word("meeting"));
+        assert_eq!(
+            SearchExpr::parse("  meeting   notes ").unwrap(),
+            SearchExpr::And(vec![word("meeting"), word("notes")])
+        );
+    }
+
+    #[test]
+    fn fields() {
+        let date = |s| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        assert_eq!(
+            SearchExpr::parse(
+                "TAG:work folder:\"Projects/Q3\" is:Pinned before:2026-02-01 after:2026-01-01"
+            )
+            .unwrap(),
+            SearchExpr::And(vec![
+                term(SearchTerm::Tag("work".to_string())),
+                term(SearchTerm::Folder(vec![
+                    "Projects".to_string(),
+                    "Q3".to_string()
+                ])),
+                term(SearchTerm::Is(NoteFlag::Pinned)),
+                term(SearchTerm::Before(date("2026-02-01"))),
+                term(SearchTerm::After(date("2026-01-01"))),
+            ])
+        );
+        // Unknown prefixes are plain words
+        assert_eq!(SearchExpr::parse("http://x").unwrap(), word("http://x"));
+    }
+
+    #[test]
+    fn folder_paths() {
+        let folder = |names: &[&str]| {
+            term(SearchTerm::Folder(
+                names.iter().map(|name| name.to_string()).collect(),
+            ))
+        };
+        assert_eq!(
+            SearchExpr::parse("folder:/Reports/Q1\\/Q2/").unwrap(),
+            folder(&["Reports", "Q1/Q2"])
+        );
+        assert_eq!(
+            error("a folder:Work//Acme"),
+            "Folder name cannot be empty at position 9"
+        );
+    }
+
+    #[test]
+    fn negation() {
+        assert_eq!(
+            SearchExpr::parse("-is:archived -(a b) - c").unwrap(),
+            SearchExpr::And(vec![
+                SearchExpr::Not(Box::new(term(SearchTerm::Is(NoteFlag::Archived)))),
+                SearchExpr::Not(Box::new(SearchExpr::And(vec![word("a"), word("b")]))),
+                // A detached dash is just a word
+                word("-"),
+                word("c"),
+            ])
+        );
+        assert_eq!(
+            SearchExpr::parse("--a").unwrap(),
+            SearchExpr::Not(Box::new(SearchExpr::Not(Box::new(word("a")))))
+        );
+    }
+
+    #[test]
+    fn phrases() {
+        assert_eq!(
+            SearchExpr::parse("\"release plan\" -\"draft\"").unwrap(),
+            SearchExpr::And(vec![
+                term(SearchTerm::Phrase("release plan".to_string())),
+                SearchExpr::Not(Box::new(term(SearchTerm::Phrase("draft".to_string())))),
+            ])
+        );
+        // A quote ends a word
+        assert_eq!(
+            SearchExpr::parse("a\"b c\"").unwrap(),
+            SearchExpr::And(vec![word("a"), term(SearchTerm::Phrase("b c".to_string()))])
+        );
+    }
+
+    #[test]
+    fn or_binds_looser_than_and() {
+        assert_eq!(
+            SearchExpr::parse("a b OR c").unwrap(),
+            SearchExpr::Or(vec![SearchExpr::And(vec![word("a"), word("b")]), word("c")])
+        );
+        assert_eq!(
+            SearchExpr::parse("a (b OR c)").unwrap(),
+            SearchExpr::And(vec![word("a"), SearchExpr::Or(vec![word("b"), word("c")])])
+        );
+        // Only upper-case OR is an operator
+        assert_eq!(
+            SearchExpr::parse("a or b").unwrap(),
+            SearchExpr::And(vec![word("a"), word("or"), word("b")])
+        );
+    }
+
+    #[test]
+    fn error_positions() {
+        assert_eq!(error(""), "Search query cannot be empty");
+        assert_eq!(error("   "), "Search query cannot be empty");
+        assert_eq!(error("a \"open"), "Unterminated quote at position 2");
+        assert_eq!(error("\"  \""), "Empty phrase at position 0");
+        assert_eq!(error("tag:"), "Expected a value after 'tag:' at position 4");
+        assert_eq!(
+            error("x folder:\"\""),
+            "Expected a value after 'folder:' at position 9"
+        );
+        assert_eq!(
+            error("is:done"),
+            "Unknown value 'done' for 'is:' (expected pinned or archived) at position 3"
+        );
+        assert_eq!(
+            error("a after:2026-13-01"),
+            "Invalid date '2026-13-01' (expected YYYY-MM-DD) at position 8"
+        );
+        assert_eq!(
+            error("OR a"),
+            "Expected a search term before 'OR' at position 0"
+        );
+        assert_eq!(error("a OR"), "Expected a search term at position 4");
+        assert_eq!(
+            error("a OR OR b"),
+            "Expected a search term before 'OR' at position 5"
+        );
+        assert_eq!(
+            error("(a b"),
+            "Missing ')' for the group opened at position 0"
+        );
+        assert_eq!(error("a b)"), "Unexpected ')' at position 3");
+        assert_eq!(
+            error("()"),
+            "Expected a search term before ')' at position 1"
+        );
+        assert_eq!(
+            error("(a -)"),
+            "Expected a search term before ')' at position 4"
+        );
+        assert_eq!(
+            error("-OR a"),
+            "Expected a search term before 'OR' at position 1"
+        );
+        // Positions count characters, not bytes
+        assert_eq!(error("ñandú \"x"), "Unterminated quote at position 6");
+    }
+
+    #[test]
+    fn rank_and_fuzzy_text_skip_filters_and_negations() {
+        let expr = SearchExpr::parse("tag:x alpha -beta \"gamma delta\" OR -(zeta)").unwrap();
+        assert_eq!(
+            expr.rank_text().as_deref(),
+            Some("alpha or \"gamma delta\"")
+        );
+        assert_eq!(expr.fuzzy_text().as_deref(), Some("alpha gamma delta"));
+
+        let filters = SearchExpr::parse("tag:x -word").unwrap();
+        assert_eq!(filters.rank_text(), None);
+        assert_eq!(filters.fuzzy_text(), None);
+    }
+}
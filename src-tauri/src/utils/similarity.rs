@@ -0,0 +1,498 @@
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+// In-memory BM25 index over the notes of one user, used to find notes that
+// talk about the same things. Each note keeps only its term counts, so the
+// index is cheap to hold and a changed note is re-indexed on its own.
+//
+// A note is compared with the others through its most distinctive terms
+// (highest TF-IDF), scored against every other note with BM25. Title words
+// count several times over, so they weigh more than words in the body.
+// Shared tags and a shared folder multiply the text score; they never make
+// an unrelated note related on their own.
+
+const K1: f32 = 1.2;
+const B: f32 = 0.75;
+const TITLE_WEIGHT: u32 = 3;
+// How many of a note's terms are used to look for related notes
+const QUERY_TERMS: usize = 30;
+// How many shared terms are reported per match
+const SHARED_TERMS: usize = 5;
+const TAG_BOOST: f32 = 0.25;
+const FOLDER_BOOST: f32 = 0.15;
+// How many similar notes vote with their tags
+const NEIGHBOURS: usize = 10;
+// A tag named in the text is a strong hint, but not proof
+const KEYWORD_CONFIDENCE: f32 = 0.8;
+const MIN_TAG_CONFIDENCE: f32 = 0.15;
+
+lazy_static! {
+    static ref URL_REGEX: Regex = Regex::new(r"\w+://\S+").unwrap();
+    static ref STOPWORDS: HashSet<&'static str> = [
+        "a", "about", "above", "after", "again", "against", "all", "also", "am", "an", "and",
+        "any", "are", "as", "at", "be", "because", "been", "before", "being", "below", "between",
+        "both", "but", "by", "can", "could", "did", "do", "does", "doing", "done", "down",
+        "during", "each", "etc", "even", "few", "for", "from", "further", "get", "got", "had",
+        "has", "have", "having", "he", "her", "here", "hers", "him", "his", "how", "i", "if", "in",
+        "into", "is", "it", "its", "just", "let", "like", "may", "me", "might", "more", "most",
+        "much", "must", "my", "no", "nor", "not", "now", "of", "off", "on", "once", "one", "only",
+        "or", "other", "our", "ours", "out", "over", "own", "per", "same", "she", "should", "so",
+        "some", "such", "than", "that", "the", "their", "theirs", "them", "then", "there", "these",
+        "they", "this", "those", "through", "to", "too", "under", "until", "up", "us", "use",
+        "used", "very", "via", "was", "we", "well", "were", "what", "when", "where", "which",
+        "while", "who", "whom", "why", "will", "with", "would", "yes", "yet", "you", "your",
+        "yours",
+    ]
+    .into_iter()
+    .collect();
+}
+
+// Lowercased words of at least two letters, without stopwords or numbers.
+// A plural `s` is dropped so "meeting" and "meetings" count as one term.
+pub fn tokenize(text: &str) -> Vec<String> {
+    words(text).into_iter().map(|(term, _)| term).collect()
+}
+
+// Each term of `tokenize` next to the word it was made from
+fn words(text: &str) -> Vec<(String, String)> {
+    let text = URL_REGEX.replace_all(text, " ");
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= 2 && !word.chars().all(|c| c.is_numeric()))
+        .map(|word| {
+            let word = word.to_lowercase();
+            (normalize(&word), word)
+        })
+        .filter(|(term, _)| !STOPWORDS.contains(term.as_str()))
+        .collect()
+}
+
+fn normalize(word: &str) -> String {
+    let singular = word.len() > 3
+        && word.ends_with('s')
+        && !word.ends_with("ss")
+        && !word.ends_with("us")
+        && !word.ends_with("is");
+    if singular {
+        word[..word.len() - 1].to_string()
+    } else {
+        word.to_string()
+    }
+}
+
+pub fn term_counts(title: &str, content: &str) -> HashMap<String, u32> {
+    let mut counts = HashMap::new();
+    for term in tokenize(title) {
+        *counts.entry(term).or_insert(0) += TITLE_WEIGHT;
+    }
+    for term in tokenize(content) {
+        *counts.entry(term).or_insert(0) += 1;
+    }
+    counts
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexedNote {
+    pub note_id: i32,
+    pub title: String,
+    pub folder_id: Option<i32>,
+    pub tags: Vec<String>,
+    pub is_archived: bool,
+    pub updated_at: DateTime<Utc>,
+    terms: HashMap<String, u32>,
+    length: u32,
+}
+
+// Everything about a note the index needs besides its text
+#[derive(Debug, Clone)]
+pub struct NoteMetadata {
+    pub note_id: i32,
+    pub folder_id: Option<i32>,
+    pub tags: Vec<String>,
+    pub is_archived: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimilarityMatch {
+    pub note_id: i32,
+    pub score: f32,
+    pub shared_terms: Vec<String>,
+    pub shared_tags: Vec<String>,
+    pub same_folder: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct TagMatch {
+    pub name: String,
+    pub confidence: f32,
+    pub matched_terms: Vec<String>,
+    pub co_occurring_tags: Vec<String>,
+    pub similar_notes: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SimilarityIndex {
+    notes: HashMap<i32, IndexedNote>,
+    doc_freq: HashMap<String, u32>,
+    total_length: u64,
+}
+
+impl SimilarityIndex {
+    pub fn get(&self, note_id: i32) -> Option<&IndexedNote> {
+        self.notes.get(&note_id)
+    }
+
+    pub fn terms(&self, note_id: i32) -> Option<&HashMap<String, u32>> {
+        self.notes.get(&note_id).map(|note| &note.terms)
+    }
+
+    // Whether the note's text has to be (re-)indexed
+    pub fn needs_text(&self, metadata: &NoteMetadata) -> bool {
+        self.notes
+            .get(&metadata.note_id)
+            .is_none_or(|note| note.updated_at != metadata.updated_at)
+    }
+
+    // Drop notes that are gone and refresh the rest's folder and tags, which
+    // can change without the note being edited
+    pub fn sync_metadata(&mut self, live: &[NoteMetadata]) {
+        let live_ids: HashSet<i32> = live.iter().map(|note| note.note_id).collect();
+        let removed: Vec<i32> = self
+            .notes
+            .keys()
+            .filter(|note_id| !live_ids.contains(note_id))
+            .copied()
+            .collect();
+        for note_id in removed {
+            self.remove(note_id);
+        }
+
+        for metadata in live {
+            if let Some(note) = self.notes.get_mut(&metadata.note_id) {
+                note.folder_id = metadata.folder_id;
+                note.tags = metadata.tags.clone();
+                note.is_archived = metadata.is_archived;
+            }
+        }
+    }
+
+    pub fn upsert(&mut self, metadata: NoteMetadata, title: String, content: &str) {
+        self.remove(metadata.note_id);
+
+        let terms = term_counts(&title, content);
+        let length = terms.values().sum();
+        for term in terms.keys() {
+            *self.doc_freq.entry(term.clone()).or_insert(0) += 1;
+        }
+        self.total_length += length as u64;
+
+        self.notes.insert(
+            metadata.note_id,
+            IndexedNote {
+                note_id: metadata.note_id,
+                title,
+                folder_id: metadata.folder_id,
+                tags: metadata.tags,
+                is_archived: metadata.is_archived,
+                updated_at: metadata.updated_at,
+                terms,
+                length,
+            },
+        );
+    }
+
+    fn remove(&mut self, note_id: i32) {
+        let Some(note) = self.notes.remove(&note_id) else {
+            return;
+        };
+        for term in note.terms.keys() {
+            if let Some(count) = self.doc_freq.get_mut(term) {
+                *count -= 1;
+                if *count == 0 {
+                    self.doc_freq.remove(term);
+                }
+            }
+        }
+        self.total_length -= note.length as u64;
+    }
+
+    // BM25 inverse document frequency; always positive
+    pub fn idf(&self, term: &str) -> f32 {
+        let total = self.notes.len() as f32;
+        let containing = self.doc_freq.get(term).copied().unwrap_or(0) as f32;
+        (1.0 + (total - containing + 0.5) / (containing + 0.5)).ln()
+    }
+
+    // The most distinctive terms of a text by TF-IDF against the indexed notes
+    pub fn key_terms(&self, terms: &HashMap<String, u32>, limit: usize) -> Vec<(String, f32)> {
+        let mut weighted: Vec<(String, f32)> = terms
+            .iter()
+            .map(|(term, &count)| (term.clone(), (1.0 + (count as f32).ln()) * self.idf(term)))
+            .collect();
+        weighted.sort_by(|a, b| by_score_desc(a.1, b.1).then_with(|| a.0.cmp(&b.0)));
+        weighted.truncate(limit);
+        weighted
+    }
+
+    // Unarchived notes most similar to `note_id`, best first
+    pub fn related(&self, note_id: i32, limit: usize) -> Vec<SimilarityMatch> {
+        let Some(source) = self.notes.get(&note_id) else {
+            return Vec::new();
+        };
+        let query = self.key_terms(&source.terms, QUERY_TERMS);
+        let average_length = self.average_length();
+
+        let mut matches: Vec<(SimilarityMatch, DateTime<Utc>)> = self
+            .notes
+            .values()
+            .filter(|note| note.note_id != note_id && !note.is_archived)
+            .filter_map(|note| {
+                let contributions = self.contributions(note, &query, average_length);
+                if contributions.is_empty() {
+                    return None;
+                }
+                let text_score: f32 = contributions.iter().map(|(_, weight)| weight).sum();
+
+                let shared_tags: Vec<String> = note
+                    .tags
+                    .iter()
+                    .filter(|tag| source.tags.iter().any(|own| own.eq_ignore_ascii_case(tag)))
+                    .cloned()
+                    .collect();
+                let same_folder = source.folder_id.is_some() && note.folder_id == source.folder_id;
+
+                let mut score = text_score * (1.0 + TAG_BOOST * shared_tags.len() as f32);
+                if same_folder {
+                    score *= 1.0 + FOLDER_BOOST;
+                }
+
+                Some((
+                    SimilarityMatch {
+                        note_id: note.note_id,
+                        score,
+                        shared_terms: contributions
+                            .iter()
+                            .take(SHARED_TERMS)
+                            .map(|(term, _)| term.to_string())
+                            .collect(),
+                        shared_tags,
+                        same_folder,
+                    },
+                    note.updated_at,
+                ))
+            })
+            .collect();
+
+        matches.sort_by(|a, b| {
+            by_score_desc(a.0.score, b.0.score)
+                .then_with(|| b.1.cmp(&a.1))
+                .then_with(|| a.0.note_id.cmp(&b.0.note_id))
+        });
+        matches.truncate(limit);
+        matches.into_iter().map(|(found, _)| found).collect()
+    }
+
+    // Tags from `vocabulary` that fit a text, best first. Three signals are
+    // combined, each a probability-like value in 0..1:
+    //
+    // - keyword: every word of the tag's name is among the text's terms,
+    //   weighted by how distinctive those terms are for the text
+    // - co-occurrence: how often the tag is found on notes that carry one of
+    //   the applied tags (or a keyword-matched tag)
+    // - neighbours: how much of the similarity mass of the most similar notes
+    //   sits on notes carrying the tag
+    //
+    // Applied tags are never suggested. `exclude_note` keeps the note the text
+    // belongs to from voting for its own tags.
+    pub fn suggest_tags(
+        &self,
+        terms: &HashMap<String, u32>,
+        applied: &[String],
+        exclude_note: Option<i32>,
+        vocabulary: &[String],
+        limit: usize,
+    ) -> Vec<TagMatch> {
+        let applied: HashSet<String> = applied.iter().map(|tag| tag.to_lowercase()).collect();
+        let spellings: HashMap<String, &String> = vocabulary
+            .iter()
+            .map(|tag| (tag.to_lowercase(), tag))
+            .collect();
+        let vocabulary: HashMap<&String, &String> = spellings
+            .iter()
+            .filter(|(key, _)| !applied.contains(*key))
+            .map(|(key, name)| (key, *name))
+            .collect();
+        let others: Vec<&IndexedNote> = self
+            .notes
+            .values()
+            .filter(|note| Some(note.note_id) != exclude_note)
+            .collect();
+
+        // Keyword: tag names spelled out in the text
+        let weights: HashMap<String, f32> = self.key_terms(terms, usize::MAX).into_iter().collect();
+        let top_weight = weights.values().copied().fold(0.0, f32::max);
+        let mut keyword: HashMap<&str, (f32, Vec<String>)> = HashMap::new();
+        for key in vocabulary.keys() {
+            let mut words = words(key);
+            words.dedup();
+            if words.is_empty() || top_weight <= 0.0 {
+                continue;
+            }
+            let Some(found) = words
+                .iter()
+                .map(|(term, _)| weights.get(term).copied())
+                .collect::<Option<Vec<f32>>>()
+            else {
+                continue;
+            };
+            let strength = found.iter().sum::<f32>() / found.len() as f32 / top_weight;
+            keyword.insert(
+                key,
+                (
+                    KEYWORD_CONFIDENCE * strength.min(1.0),
+                    words.into_iter().map(|(_, word)| word).collect(),
+                ),
+            );
+        }
+
+        // Co-occurrence with the applied tags, and more weakly with the
+        // keyword matches
+        let mut seeds: HashMap<String, f32> =
+            applied.iter().map(|tag| (tag.clone(), 1.0)).collect();
+        for (key, (strength, _)) in &keyword {
+            seeds.entry(key.to_string()).or_insert(*strength);
+        }
+        let mut seed_notes: HashMap<&str, u32> = HashMap::new();
+        let mut pairs: HashMap<(&str, String), u32> = HashMap::new();
+        for note in &others {
+            let tags: Vec<String> = note.tags.iter().map(|tag| tag.to_lowercase()).collect();
+            for seed in tags
+                .iter()
+                .filter_map(|tag| seeds.get_key_value(tag))
+                .map(|(seed, _)| seed)
+            {
+                *seed_notes.entry(seed.as_str()).or_insert(0) += 1;
+                for tag in tags.iter().filter(|tag| *tag != seed) {
+                    *pairs.entry((seed.as_str(), tag.clone())).or_insert(0) += 1;
+                }
+            }
+        }
+        // Each candidate's seeds with the share of their notes that carry it
+        let mut co_occurrence: HashMap<&str, Vec<(String, f32)>> = HashMap::new();
+        for ((seed, tag), together) in &pairs {
+            let Some((key, _)) = vocabulary.get_key_value(tag) else {
+                continue;
+            };
+            // One extra note in the denominator keeps a single shared note
+            // from reading as certainty
+            let share = seeds[*seed] * *together as f32 / (seed_notes[seed] + 1) as f32;
+            co_occurrence
+                .entry(key.as_str())
+                .or_default()
+                .push((seed.to_string(), share));
+        }
+
+        // Neighbours: tags of the notes most similar to the text
+        let query = self.key_terms(terms, QUERY_TERMS);
+        let average_length = self.average_length();
+        let mut similar: Vec<(&IndexedNote, f32)> = others
+            .iter()
+            .map(|note| {
+                let score = self
+                    .contributions(note, &query, average_length)
+                    .iter()
+                    .map(|(_, weight)| weight)
+                    .sum();
+                (*note, score)
+            })
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+        similar.sort_by(|a, b| by_score_desc(a.1, b.1).then_with(|| a.0.note_id.cmp(&b.0.note_id)));
+        similar.truncate(NEIGHBOURS);
+        let total: f32 = similar.iter().map(|(_, score)| score).sum();
+        // As if one more, average neighbour lacked every tag, so that a
+        // single similar note cannot carry full confidence
+        let smoothed_total = total + total / similar.len().max(1) as f32;
+        let mut neighbours: HashMap<&str, (f32, u32)> = HashMap::new();
+        for (note, score) in &similar {
+            for tag in &note.tags {
+                if let Some((key, _)) = vocabulary.get_key_value(&tag.to_lowercase()) {
+                    let entry = neighbours.entry(key.as_str()).or_insert((0.0, 0));
+                    entry.0 += score / smoothed_total;
+                    entry.1 += 1;
+                }
+            }
+        }
+
+        let mut matches: Vec<TagMatch> = vocabulary
+            .iter()
+            .filter_map(|(key, name)| {
+                let (keyword, matched_terms) = keyword.remove(key.as_str()).unwrap_or_default();
+                let mut co_occurring = co_occurrence.remove(key.as_str()).unwrap_or_default();
+                let co_occurrence = co_occurring
+                    .iter()
+                    .map(|(_, share)| *share)
+                    .fold(0.0, f32::max);
+                let (neighbours, similar_notes) =
+                    neighbours.remove(key.as_str()).unwrap_or_default();
+
+                let confidence = 1.0
+                    - (1.0 - keyword)
+                        * (1.0 - co_occurrence.min(1.0))
+                        * (1.0 - neighbours.min(1.0));
+                if confidence < MIN_TAG_CONFIDENCE {
+                    return None;
+                }
+
+                co_occurring.sort_by(|a, b| by_score_desc(a.1, b.1).then_with(|| a.0.cmp(&b.0)));
+                Some(TagMatch {
+                    name: name.to_string(),
+                    confidence,
+                    matched_terms,
+                    co_occurring_tags: co_occurring
+                        .into_iter()
+                        .map(|(seed, _)| spellings.get(&seed).map_or(seed, |name| name.to_string()))
+                        .collect(),
+                    similar_notes,
+                })
+            })
+            .collect();
+
+        matches.sort_by(|a, b| {
+            by_score_desc(a.confidence, b.confidence).then_with(|| a.name.cmp(&b.name))
+        });
+        matches.truncate(limit);
+        matches
+    }
+
+    fn average_length(&self) -> f32 {
+        (self.total_length as f32 / self.notes.len().max(1) as f32).max(1.0)
+    }
+
+    // The BM25 weight of each query term found in the note, largest first
+    fn contributions<'a>(
+        &self,
+        note: &IndexedNote,
+        query: &'a [(String, f32)],
+        average_length: f32,
+    ) -> Vec<(&'a str, f32)> {
+        let normalizer = K1 * (1.0 - B + B * note.length as f32 / average_length);
+        let mut contributions: Vec<(&str, f32)> = query
+            .iter()
+            .filter_map(|(term, _)| {
+                let count = *note.terms.get(term)? as f32;
+                let weight = self.idf(term) * count * (K1 + 1.0) / (count + normalizer);
+                Some((term.as_str(), weight))
+            })
+            .collect();
+        contributions.sort_by(|a, b| by_score_desc(a.1, b.1).then_with(|| a.0.cmp(b.0)));
+        contributions
+    }
+}
+
+fn by_score_desc(a: f32, b: f32) -> Ordering {
+    b.partial_cmp(&a).unwrap_or(Ordering::Equal)
+}
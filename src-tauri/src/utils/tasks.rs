@@ -0,0 +1,196 @@
+use chrono::NaiveDate;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+// Markdown checkbox items: `- [ ] open`, `* [x] done`, `1. [X] numbered`.
+// An `@due(2026-10-20)` annotation anywhere in the item sets its due date
+// and is left out of the task text. Items inside fenced code blocks are
+// ignored.
+
+lazy_static! {
+    static ref TASK_REGEX: Regex =
+        Regex::new(r"^\s*(?:[-*+]|\d+[.)])\s+\[([ xX])\](?:\s+(.*))?$").unwrap();
+    static ref DUE_REGEX: Regex = Regex::new(r"@due\((\d{4}-\d{2}-\d{2})\)").unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskItem {
+    // 1-based line number in the note content
+    pub line_number: usize,
+    pub text: String,
+    pub is_done: bool,
+    pub due_date: Option<NaiveDate>,
+}
+
+pub fn parse_tasks(content: &str) -> Vec<TaskItem> {
+    let mut tasks = Vec::new();
+    let mut fence: Option<&str> = None;
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let marker = if trimmed.starts_with("```") {
+            Some("```")
+        } else if trimmed.starts_with("~~~") {
+            Some("~~~")
+        } else {
+            None
+        };
+
+        match (fence, marker) {
+            (None, Some(marker)) => {
+                fence = Some(marker);
+                continue;
+            }
+            (Some(open), Some(marker)) if open == marker => {
+                fence = None;
+                continue;
+            }
+            (Some(_), _) => continue,
+            (None, None) => {}
+        }
+
+        let Some(captures) = TASK_REGEX.captures(line) else {
+            continue;
+        };
+        let body = captures.get(2).map_or("", |m| m.as_str());
+
+        let due_date = DUE_REGEX
+            .captures_iter(body)
+            .find_map(|due| NaiveDate::parse_from_str(&due[1], "%Y-%m-%d").ok());
+        let text = match due_date {
+            Some(_) => DUE_REGEX
+                .replace_all(body, "")
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+            None => body.trim().to_string(),
+        };
+
+        tasks.push(TaskItem {
+            line_number: index + 1,
+            text,
+            is_done: &captures[1] != " ",
+            due_date,
+        });
+    }
+
+    tasks
+}
+
+// Flip the checkbox on `line_number` (1-based). Returns the new content and
+// whether the task is now done, or `None` when that line is not a task.
+pub fn toggle_task_line(content: &str, line_number: usize) -> Option<(String, bool)> {
+    // Checkbox syntax inside a code block is not a task
+    if !parse_tasks(content)
+        .iter()
+        .any(|task| task.line_number == line_number)
+    {
+        return None;
+    }
+
+    let mut toggled = String::with_capacity(content.len());
+    let mut is_done = None;
+
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        if index + 1 != line_number {
+            toggled.push_str(line);
+            continue;
+        }
+
+        let body = line.trim_end_matches(['\r', '\n']);
+        let checkbox = TASK_REGEX.captures(body)?.get(1)?;
+        let done = checkbox.as_str() == " ";
+
+        toggled.push_str(&line[..checkbox.start()]);
+        toggled.push(if done { 'x' } else { ' ' });
+        toggled.push_str(&line[checkbox.end()..]);
+        is_done = Some(done);
+    }
+
+    is_done.map(|done| (toggled, done))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn list_markers_and_states() {
+        let content = "\
+- [ ] open
+* [x] done
++ [X] shouted
+1. [ ] numbered
+  2) [ ]  indented
+-[ ] no space
+- [] no box
+- [ ]
+text - [ ] not at start
+";
+        let tasks = parse_tasks(content);
+        let summary: Vec<(usize, &str, bool)> = tasks
+            .iter()
+            .map(|task| (task.line_number, task.text.as_str(), task.is_done))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, "open", false),
+                (2, "done", true),
+                (3, "shouted", true),
+                (4, "numbered", false),
+                (5, "indented", false),
+                (8, "", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn due_dates() {
+        let tasks = parse_tasks(
+            "- [ ] ship @due(2026-10-20) it\n- [ ] bad @due(2026-02-30)\n- [ ] two @due(x) @due(2026-01-02)",
+        );
+        assert_eq!(tasks[0].text, "ship it");
+        assert_eq!(tasks[0].due_date, Some(date("2026-10-20")));
+        // An impossible date is kept as text
+        assert_eq!(tasks[1].text, "bad @due(2026-02-30)");
+        assert_eq!(tasks[1].due_date, None);
+        assert_eq!(tasks[2].text, "two @due(x)");
+        assert_eq!(tasks[2].due_date, Some(date("2026-01-02")));
+    }
+
+    #[test]
+    fn fenced_code_is_skipped() {
+        let content = "```\n- [ ] in code\n~~~\n- [ ] still code\n```\n- [ ] real\r\n~~~md\n- [ ] tilde\n~~~\n";
+        let tasks = parse_tasks(content);
+        assert_eq!(tasks.len(), 1);
+        assert_eq!((tasks[0].line_number, tasks[0].text.as_str()), (6, "real"));
+    }
+
+    #[test]
+    fn toggling() {
+        let content = "# List\n- [ ] one\r\n- [x] two\n```\n- [ ] code\n```";
+        assert_eq!(
+            toggle_task_line(content, 2),
+            Some((
+                "# List\n- [x] one\r\n- [x] two\n```\n- [ ] code\n```".to_string(),
+                true
+            ))
+        );
+        assert_eq!(
+            toggle_task_line(content, 3),
+            Some((
+                "# List\n- [ ] one\r\n- [ ] two\n```\n- [ ] code\n```".to_string(),
+                false
+            ))
+        );
+        assert_eq!(toggle_task_line(content, 1), None);
+        assert_eq!(toggle_task_line(content, 5), None);
+        assert_eq!(toggle_task_line(content, 0), None);
+        assert_eq!(toggle_task_line(content, 99), None);
+    }
+}
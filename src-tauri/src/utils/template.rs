@@ -0,0 +1,126 @@
+use crate::utils::error::{AppError, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+// Placeholders look like `{{name}}`. `{{date}}` and `{{time}}` take an
+// optional chrono format after a colon, e.g. `{{date:%A, %B %d}}`. Any other
+// name must be declared as one of the template's custom prompts.
+lazy_static! {
+    static ref VARIABLE_REGEX: Regex =
+        Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*(?::([^}]*))?\}\}").unwrap();
+    static ref PROMPT_NAME_REGEX: Regex = Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap();
+}
+
+pub const BUILTIN_VARIABLES: &[&str] = &["date", "time", "title", "folder"];
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+const DEFAULT_TIME_FORMAT: &str = "%H:%M";
+
+pub struct TemplateContext<'a> {
+    pub now: DateTime<Local>,
+    pub title: &'a str,
+    pub folder: &'a str,
+    // Answers to the template's custom prompts, by variable name
+    pub values: &'a HashMap<String, String>,
+}
+
+// Check that every placeholder in `text` is a built-in or one of `prompts`,
+// and that date and time formats are valid
+pub fn validate_template(text: &str, prompts: &[&str]) -> Result<()> {
+    for captures in VARIABLE_REGEX.captures_iter(text) {
+        let name = &captures[1];
+        let format = captures.get(2).map(|m| m.as_str().trim());
+
+        if !BUILTIN_VARIABLES.contains(&name) && !prompts.contains(&name) {
+            return Err(AppError::ValidationError(format!(
+                "Unknown template variable '{}'",
+                &captures[0]
+            )));
+        }
+
+        if let Some(format) = format {
+            if name != "date" && name != "time" {
+                return Err(AppError::ValidationError(format!(
+                    "Only {{{{date}}}} and {{{{time}}}} take a format, found '{}'",
+                    &captures[0]
+                )));
+            }
+            validate_date_format(format)?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn validate_prompt_name(name: &str) -> Result<()> {
+    if !PROMPT_NAME_REGEX.is_match(name) {
+        return Err(AppError::ValidationError(format!(
+            "Prompt name '{}' must start with a letter or underscore and contain only letters, digits and underscores",
+            name
+        )));
+    }
+    if BUILTIN_VARIABLES.contains(&name) {
+        return Err(AppError::ValidationError(format!(
+            "Prompt name '{}' is reserved",
+            name
+        )));
+    }
+    Ok(())
+}
+
+pub fn render_template(text: &str, context: &TemplateContext) -> Result<String> {
+    let mut rendered = String::with_capacity(text.len());
+    let mut last = 0;
+
+    for captures in VARIABLE_REGEX.captures_iter(text) {
+        let whole = captures.get(0).unwrap();
+        let name = &captures[1];
+        let format = captures.get(2).map(|m| m.as_str().trim());
+
+        rendered.push_str(&text[last..whole.start()]);
+        last = whole.end();
+
+        match name {
+            "date" => format_now(
+                &mut rendered,
+                context.now,
+                format.unwrap_or(DEFAULT_DATE_FORMAT),
+            )?,
+            "time" => format_now(
+                &mut rendered,
+                context.now,
+                format.unwrap_or(DEFAULT_TIME_FORMAT),
+            )?,
+            "title" => rendered.push_str(context.title),
+            "folder" => rendered.push_str(context.folder),
+            _ => match context.values.get(name) {
+                Some(value) => rendered.push_str(value),
+                // Not a variable this template knows; keep the text as written
+                None => rendered.push_str(whole.as_str()),
+            },
+        }
+    }
+
+    rendered.push_str(&text[last..]);
+    Ok(rendered)
+}
+
+pub fn validate_date_format(format: &str) -> Result<()> {
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(AppError::ValidationError(format!(
+            "Invalid date format '{}'",
+            format
+        )));
+    }
+    Ok(())
+}
+
+fn format_now(out: &mut String, now: DateTime<Local>, format: &str) -> Result<()> {
+    validate_date_format(format)?;
+    write!(out, "{}", now.format(format))
+        .map_err(|_| AppError::ValidationError(format!("Invalid date format '{}'", format)))
+}
@@ -0,0 +1,231 @@
+// `[[Note Title]]` and `[[Note Title|alias]]` references between notes.
+// Links inside fenced code blocks and inline code spans are ignored, and a
+// link never spans more than one line.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WikiLink {
+    pub target: String,
+    pub alias: Option<String>,
+    // Byte range of the whole `[[...]]` in the content
+    pub start: usize,
+    pub end: usize,
+    // The trimmed line the link sits on, shown next to backlinks
+    pub context: String,
+}
+
+pub fn parse_wiki_links(content: &str) -> Vec<WikiLink> {
+    let mut links = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut line_start = 0;
+
+    for line in content.split_inclusive('\n') {
+        let offset = line_start;
+        line_start += line.len();
+
+        let trimmed = line.trim_start();
+        let marker = if trimmed.starts_with("```") {
+            Some("```")
+        } else if trimmed.starts_with("~~~") {
+            Some("~~~")
+        } else {
+            None
+        };
+
+        match (fence, marker) {
+            (None, Some(marker)) => {
+                fence = Some(marker);
+                continue;
+            }
+            (Some(open), Some(marker)) if open == marker => {
+                fence = None;
+                continue;
+            }
+            (Some(_), _) => continue,
+            (None, None) => {}
+        }
+
+        for (start, end, inner) in scan_line(line) {
+            let (target, alias) = match inner.split_once('|') {
+                Some((target, alias)) => (target.trim(), Some(alias.trim())),
+                None => (inner.trim(), None),
+            };
+            if target.is_empty() {
+                continue;
+            }
+
+            links.push(WikiLink {
+                target: target.to_string(),
+                alias: alias.filter(|a| !a.is_empty()).map(str::to_string),
+                start: offset + start,
+                end: offset + end,
+                context: line.trim().to_string(),
+            });
+        }
+    }
+
+    links
+}
+
+// Point every link to `old_title` (compared case-insensitively) at
+// `new_title`, keeping aliases. Returns the new content and how many links
+// were rewritten.
+pub fn rewrite_wiki_links(content: &str, old_title: &str, new_title: &str) -> (String, usize) {
+    let old_title = old_title.trim().to_lowercase();
+    let mut rewritten = String::with_capacity(content.len());
+    let mut last = 0;
+    let mut count = 0;
+
+    for link in parse_wiki_links(content) {
+        if link.target.to_lowercase() != old_title {
+            continue;
+        }
+
+        rewritten.push_str(&content[last..link.start]);
+        rewritten.push_str("[[");
+        rewritten.push_str(new_title.trim());
+        if let Some(alias) = &link.alias {
+            rewritten.push('|');
+            rewritten.push_str(alias);
+        }
+        rewritten.push_str("]]");
+        last = link.end;
+        count += 1;
+    }
+
+    rewritten.push_str(&content[last..]);
+    (rewritten, count)
+}
+
+// Whether `[[title]]` would parse back to the same title
+pub fn is_linkable_title(title: &str) -> bool {
+    !title.trim().is_empty()
+        && !title.contains('|')
+        && !title.contains("[[")
+        && !title.contains("]]")
+        && !title.contains('\n')
+}
+
+// Byte ranges and inner text of every `[[...]]` on a line outside inline code
+fn scan_line(line: &str) -> Vec<(usize, usize, &str)> {
+    let bytes = line.as_bytes();
+    let mut found = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'`' => {
+                // Skip an inline code span: a run of backticks up to the next run of the same length
+                let run = bytes[i..].iter().take_while(|&&b| b == b'`').count();
+                let fence = &line[i..i + run];
+                match line[i + run..].find(fence) {
+                    Some(close) => i += run + close + run,
+                    None => i += run,
+                }
+            }
+            b'[' if bytes.get(i + 1) == Some(&b'[') => {
+                let body = &line[i + 2..];
+                let close = body.find("]]");
+                let nested = body.find("[[");
+
+                match close {
+                    Some(close) if nested.is_none_or(|nested| nested > close) => {
+                        found.push((i, i + 2 + close + 2, &body[..close]));
+                        i += 2 + close + 2;
+                    }
+                    _ => i += 1,
+                }
+            }
+            _ => i += 1,
+        }
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn targets(content: &str) -> Vec<(String, Option<String>)> {
+        parse_wiki_links(content)
+            .into_iter()
+            .map(|link| (link.target, link.alias))
+            .collect()
+    }
+
+    #[test]
+    fn links_and_aliases() {
+        assert_eq!(
+            targets("See [[ Alpha ]] and [[Beta|the second]] or [[Gamma| ]]."),
+            vec![
+                ("Alpha".to_string(), None),
+                ("Beta".to_string(), Some("the second".to_string())),
+                ("Gamma".to_string(), None),
+            ]
+        );
+        assert!(targets("[[]] [[ | alias]] [[ ]]").is_empty());
+    }
+
+    #[test]
+    fn byte_ranges_and_context() {
+        let content = "first line\n  ünïcode [[Target|x]] here  \n";
+        let links = parse_wiki_links(content);
+        assert_eq!(links.len(), 1);
+        assert_eq!(&content[links[0].start..links[0].end], "[[Target|x]]");
+        assert_eq!(links[0].context, "ünïcode [[Target|x]] here");
+    }
+
+    #[test]
+    fn code_is_skipped() {
+        let content = "\
+[[Before]]
+```rust
+let x = [[Inside fence]];
+```
+~~~
+[[Tilde fence]]
+```
+still inside the tilde fence
+~~~
+`[[Inline]]` and ``a ` [[Double]]`` then [[After]]
+an unclosed ` tick [[Still found]]
+";
+        assert_eq!(
+            targets(content)
+                .into_iter()
+                .map(|(target, _)| target)
+                .collect::<Vec<_>>(),
+            vec!["Before", "After", "Still found"]
+        );
+    }
+
+    #[test]
+    fn malformed_brackets() {
+        // The innermost complete pair wins, and links never span lines
+        assert_eq!(targets("[[a [[b]]"), vec![("b".to_string(), None)]);
+        assert!(targets("[[open\nclose]]").is_empty());
+        assert!(targets("[single] [[unclosed").is_empty());
+    }
+
+    #[test]
+    fn rewrite_keeps_aliases_and_code() {
+        let content = "[[old note]] and [[Old Note|alias]], `[[Old Note]]`, [[Other]]";
+        let (rewritten, count) = rewrite_wiki_links(content, " Old Note ", " New ");
+        assert_eq!(count, 2);
+        assert_eq!(
+            rewritten,
+            "[[New]] and [[New|alias]], `[[Old Note]]`, [[Other]]"
+        );
+
+        let (unchanged, count) = rewrite_wiki_links(content, "Missing", "New");
+        assert_eq!((unchanged.as_str(), count), (content, 0));
+    }
+
+    #[test]
+    fn linkable_titles() {
+        assert!(is_linkable_title("Meeting notes"));
+        for title in ["", "  ", "a|b", "a[[b", "a]]b", "two\nlines"] {
+            assert!(!is_linkable_title(title), "{title:?}");
+        }
+    }
+}